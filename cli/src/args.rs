@@ -0,0 +1,670 @@
+use std::slice::Iter;
+use std::str::FromStr;
+
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::config::{AccountConfig, DisputeWindow};
+use simledger::fee::{Fee, FeeRule, FeeSchedule};
+use simledger::fx::Rounding;
+use simledger::id::RawClientId;
+use simledger::rules::VelocityLimit;
+use simledger::policy::ThresholdPolicy;
+use simledger::precision::{AssetScales, InputRounding};
+use simledger::retention::Retention;
+use simledger::risk::RiskConfig;
+
+use crate::runner::RunnerError;
+use crate::unwrap_or_err;
+
+#[derive(Debug)]
+pub enum Command {
+    Summary {
+        file_name: String,
+    },
+    BalanceAt {
+        file_name: String,
+        client_id: RawClientId,
+        seq: u64,
+    },
+    Statement {
+        file_name: String,
+        client_id: Option<RawClientId>,
+    },
+    Cases {
+        file_name: String,
+        client_id: Option<RawClientId>,
+    },
+    TrialBalance {
+        file_name: String,
+    },
+}
+
+impl Command {
+    pub fn file_name(&self) -> &str {
+        match self {
+            Command::Summary { file_name }
+            | Command::BalanceAt { file_name, .. }
+            | Command::Statement { file_name, .. }
+            | Command::Cases { file_name, .. }
+            | Command::TrialBalance { file_name } => file_name,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub account_config: AccountConfig,
+    pub policy: ThresholdPolicy,
+    pub fee_schedule: FeeSchedule,
+    pub risk_config: RiskConfig,
+
+    // decimal places per asset of amounts read and written
+    pub scales: AssetScales,
+    // applied to input amounts with more decimal places than their asset
+    pub rounding: InputRounding,
+
+    // csv file with client and limit columns
+    pub credit_limits_file: Option<String>,
+    // csv file with pair, rate and seq columns
+    pub fx_rates_file: Option<String>,
+    pub fx_rounding: Rounding,
+    // csv file receiving the flagged accounts of the summary
+    pub flagged_report_file: Option<String>,
+
+    // accounts beyond the cached ones are written out to this directory
+    pub spill_dir: Option<String>,
+    pub cached_accounts: Option<usize>,
+
+    // peak heap usage and retained entries are printed to stderr
+    pub memory_stats: bool,
+
+    // summary rows carry every balance column, not just the original ones
+    pub extended_summary: bool,
+
+    // number of consecutive rows applied all or nothing, if set
+    pub atomic_batch: Option<usize>,
+}
+
+fn parse_value<T: FromStr>(
+    value: &str,
+    name: &str,
+) -> Result<T, RunnerError> {
+    let value: T =
+        unwrap_or_err!(
+            value.parse(),
+            RunnerError::InvalidArgument(
+                format!(
+                    "{:?} could not be parsed ({})",
+                    value,
+                    name,
+                ),
+            )
+        );
+
+    Ok(value)
+}
+
+fn next_value<'a>(
+    args: &mut Iter<'a, String>,
+    name: &str,
+) -> Result<&'a String, RunnerError> {
+    match args.next() {
+        None => Err(
+            RunnerError::InvalidArgument(
+                format!(
+                    "{} requires a value",
+                    name,
+                ),
+            ),
+        ),
+        Some(value) => Ok(value),
+    }
+}
+
+// amounts given as options must fit the precision of their asset
+fn parse_amount(
+    value: &str,
+    scales: &AssetScales,
+    asset: Asset,
+    name: &str,
+) -> Result<Amount, RunnerError> {
+    let amount =
+        unwrap_or_err!(
+            scales.parse_amount(asset, value, InputRounding::Reject),
+            RunnerError::InvalidArgument(
+                format!(
+                    "{:?} could not be parsed ({})",
+                    value,
+                    name,
+                ),
+            )
+        );
+
+    Ok(amount.amount)
+}
+
+fn parse_asset(
+    value: &str,
+    name: &str,
+) -> Result<Asset, RunnerError> {
+    match Asset::new(value) {
+        None => Err(
+            RunnerError::InvalidArgument(
+                format!(
+                    "{:?} is not a valid asset code ({})",
+                    value,
+                    name,
+                ),
+            ),
+        ),
+        Some(asset) => Ok(asset),
+    }
+}
+
+// <max withdrawals>/<transactions or seconds>
+fn parse_velocity(
+    value: &str,
+    name: &str,
+) -> Result<VelocityLimit, RunnerError> {
+    match value.find('/') {
+        None => {
+            Err(
+                RunnerError::InvalidArgument(
+                    format!(
+                        "{:?} could not be parsed ({})",
+                        value,
+                        name,
+                    ),
+                ),
+            )
+        }
+
+        Some(n) => {
+            Ok(
+                VelocityLimit {
+                    max_withdrawals: parse_value(&value[..n], name)?,
+                    per: parse_value(&value[n + 1..], name)?,
+                },
+            )
+        }
+    }
+}
+
+// flat amounts and percentages joined by '+', e.g. 0.5+1.5%
+fn parse_fee(
+    value: &str,
+    scales: &AssetScales,
+    asset: Asset,
+    name: &str,
+) -> Result<Fee, RunnerError> {
+    let mut fee = Fee::flat(0);
+
+    for part in value.split('+') {
+        let part = part.trim();
+
+        let sum =
+            if let Some(percentage) = part.strip_suffix('%') {
+                let basis_points =
+                    unwrap_or_err!(
+                        Fee::parse_basis_points(percentage),
+                        RunnerError::InvalidArgument(
+                            format!(
+                                "{:?} is not a percentage with at most two decimal places ({})",
+                                percentage,
+                                name,
+                            ),
+                        )
+                    );
+
+                fee.basis_points.checked_add(basis_points)
+                    .map(|basis_points| fee.basis_points = basis_points)
+            } else {
+                fee.flat.checked_add(parse_amount(part, scales, asset, name)?)
+                    .map(|flat| fee.flat = flat)
+            };
+
+        if sum.is_none() {
+            return Err(
+                RunnerError::InvalidArgument(
+                    format!("{:?} adds up to more than a fee can be ({})", value, name),
+                ),
+            );
+        }
+    }
+
+    Ok(fee)
+}
+
+// <type>=<fee> or <type>=<fee>;<from>:<fee>;... for tiered fees
+fn parse_fee_rule(
+    value: &str,
+    options: &mut Options,
+    name: &str,
+) -> Result<(), RunnerError> {
+    // flat fees and tier bounds are in the base asset, the ledger
+    // converts them for transactions in other assets
+    let scales = &options.scales;
+    let asset = options.account_config.base_asset;
+
+    let (tx_type, rule) =
+        match value.find('=') {
+            None => {
+                return Err(
+                    RunnerError::InvalidArgument(
+                        format!(
+                            "{:?} could not be parsed ({})",
+                            value,
+                            name,
+                        ),
+                    ),
+                );
+            }
+            Some(n) => (&value[..n], &value[n + 1..]),
+        };
+
+    let mut tiers = Vec::new();
+
+    for tier in rule.split(';') {
+        match tier.find(':') {
+            None => tiers.push((0, parse_fee(tier, scales, asset, name)?)),
+            Some(n) => {
+                tiers.push(
+                    (
+                        parse_amount(&tier[..n], scales, asset, name)?,
+                        parse_fee(&tier[n + 1..], scales, asset, name)?,
+                    ),
+                );
+            }
+        }
+    }
+
+    let rule =
+        match tiers.as_slice() {
+            [(0, fee)] => FeeRule::Fixed(*fee),
+            _ => FeeRule::Tiered(tiers),
+        };
+
+    let schedule = &mut options.fee_schedule;
+
+    let slot =
+        match tx_type {
+            "deposit" => &mut schedule.deposit,
+            "withdrawal" => &mut schedule.withdrawal,
+            "transfer" => &mut schedule.transfer,
+            "capture" => &mut schedule.capture,
+            "chargeback" => &mut schedule.chargeback,
+
+            val => {
+                return Err(
+                    RunnerError::InvalidArgument(
+                        format!(
+                            "{} is not a transaction type with fees ({})",
+                            val,
+                            name,
+                        ),
+                    ),
+                );
+            }
+        };
+
+    *slot = Some(rule);
+
+    Ok(())
+}
+
+fn parse_command(
+    args: &[String],
+) -> Result<Command, RunnerError> {
+    match args {
+        [file_name] => {
+            Ok(
+                Command::Summary {
+                    file_name: file_name.clone(),
+                },
+            )
+        }
+
+        [command, file_name, client, seq] if command == "balance-at" => {
+            Ok(
+                Command::BalanceAt {
+                    file_name: file_name.clone(),
+                    client_id: parse_value(client, "client")?,
+                    seq: parse_value(seq, "seq")?,
+                },
+            )
+        }
+
+        [command, file_name] if command == "statement" => {
+            Ok(
+                Command::Statement {
+                    file_name: file_name.clone(),
+                    client_id: None,
+                },
+            )
+        }
+
+        [command, file_name, client] if command == "statement" => {
+            Ok(
+                Command::Statement {
+                    file_name: file_name.clone(),
+                    client_id: Some(parse_value(client, "client")?),
+                },
+            )
+        }
+
+        [command, file_name] if command == "cases" => {
+            Ok(
+                Command::Cases {
+                    file_name: file_name.clone(),
+                    client_id: None,
+                },
+            )
+        }
+
+        [command, file_name, client] if command == "cases" => {
+            Ok(
+                Command::Cases {
+                    file_name: file_name.clone(),
+                    client_id: Some(parse_value(client, "client")?),
+                },
+            )
+        }
+
+        [command, file_name] if command == "trial-balance" => {
+            Ok(
+                Command::TrialBalance {
+                    file_name: file_name.clone(),
+                },
+            )
+        }
+
+        _ => Err(RunnerError::InvalidArgument(String::from("unknown command"))),
+    }
+}
+
+pub fn parse_args(
+    args: &[String],
+) -> Result<(Command, Options), RunnerError> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    // amounts depend on the base asset and its decimals, which
+    // may be given later on, and are parsed after all other options
+    let mut amount_args = Vec::new();
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dispute-window" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.account_config.dispute_window =
+                    DisputeWindow::Seconds(
+                        parse_value(value, arg)?,
+                    );
+            }
+
+            "--max-disputes" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.account_config.max_disputes =
+                    Some(parse_value(value, arg)?);
+            }
+
+            "--lock-after-chargebacks" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.policy.lock_after_chargebacks =
+                    parse_value(value, arg)?;
+            }
+
+            "--forbid-negative-disputes" => {
+                options.policy.allow_negative_available = false;
+            }
+
+            "--block-on-deficit" => {
+                options.policy.block_on_deficit = true;
+            }
+
+            "--unlock-on-representment" => {
+                options.account_config.unlock_on_representment = true;
+            }
+
+            "--base-asset" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.account_config.base_asset = parse_asset(value, arg)?;
+            }
+
+            "--decimals" => {
+                let value = next_value(&mut args, arg)?;
+
+                // <asset>=<decimal places>, e.g. JPY=0
+                let (asset, decimals) =
+                    match value.find('=') {
+                        None => {
+                            return Err(
+                                RunnerError::InvalidArgument(
+                                    format!(
+                                        "{:?} could not be parsed ({})",
+                                        value,
+                                        arg,
+                                    ),
+                                ),
+                            );
+                        }
+                        Some(n) => (&value[..n], &value[n + 1..]),
+                    };
+
+                unwrap_or_err!(
+                    options.scales.set_decimals(
+                        parse_asset(asset, arg)?,
+                        parse_value(decimals, arg)?,
+                    ),
+                    RunnerError::InvalidArgument(
+                        format!(
+                            "{} decimal places are not supported ({})",
+                            decimals,
+                            arg,
+                        ),
+                    )
+                );
+            }
+
+            "--rounding" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.rounding =
+                    match value.as_str() {
+                        "reject" => InputRounding::Reject,
+                        "half-even" => InputRounding::HalfEven,
+                        "truncate" => InputRounding::Truncate,
+
+                        val => {
+                            return Err(
+                                RunnerError::InvalidArgument(
+                                    format!(
+                                        "{} is not a rounding mode ({})",
+                                        val,
+                                        arg,
+                                    ),
+                                ),
+                            );
+                        }
+                    };
+            }
+
+            "--credit-limit"
+            | "--max-withdrawal"
+            | "--daily-withdrawal-cap"
+            | "--fee" => {
+                let value = next_value(&mut args, arg)?;
+
+                amount_args.push((arg, value));
+            }
+
+            "--velocity-transactions" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.account_config.withdrawal_rules.velocity_per_transactions =
+                    Some(parse_velocity(value, arg)?);
+            }
+
+            "--velocity-seconds" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.account_config.withdrawal_rules.velocity_per_seconds =
+                    Some(parse_velocity(value, arg)?);
+            }
+
+            "--credit-limits" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.credit_limits_file = Some(value.clone());
+            }
+
+            "--risk-threshold" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.risk_config.flag_threshold = parse_value(value, arg)?;
+            }
+
+            "--flagged-report" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.flagged_report_file = Some(value.clone());
+            }
+
+            "--fx-rates" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.fx_rates_file = Some(value.clone());
+            }
+
+            "--fx-rounding" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.fx_rounding =
+                    match value.as_str() {
+                        "down" => Rounding::Down,
+                        "up" => Rounding::Up,
+                        "half-even" => Rounding::HalfEven,
+
+                        val => {
+                            return Err(
+                                RunnerError::InvalidArgument(
+                                    format!(
+                                        "{} is not a rounding mode ({})",
+                                        val,
+                                        arg,
+                                    ),
+                                ),
+                            );
+                        }
+                    };
+            }
+
+            "--retention" => {
+                let value = next_value(&mut args, arg)?;
+
+                // count=<entries>, age=<transactions> or dispute-window
+                options.account_config.retention =
+                    match value.find('=') {
+                        None if value == "dispute-window" => Retention::DisputeWindow,
+                        Some(n) if &value[..n] == "count" => Retention::Count(parse_value(&value[n + 1..], arg)?),
+                        Some(n) if &value[..n] == "age" => Retention::Age(parse_value(&value[n + 1..], arg)?),
+
+                        _ => {
+                            return Err(
+                                RunnerError::InvalidArgument(
+                                    format!(
+                                        "{} is not a retention ({})",
+                                        value,
+                                        arg,
+                                    ),
+                                ),
+                            );
+                        }
+                    };
+            }
+
+            "--extended-summary" => {
+                options.extended_summary = true;
+            }
+
+            "--memory-stats" => {
+                options.memory_stats = true;
+            }
+
+            "--atomic-batch" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.atomic_batch =
+                    match parse_value(value, arg)? {
+                        0 => {
+                            return Err(
+                                RunnerError::InvalidArgument(
+                                    format!(
+                                        "batches must hold at least one row ({})",
+                                        arg,
+                                    ),
+                                ),
+                            );
+                        }
+                        size => Some(size),
+                    };
+            }
+
+            "--spill-dir" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.spill_dir = Some(value.clone());
+            }
+
+            "--cached-accounts" => {
+                let value = next_value(&mut args, arg)?;
+
+                options.cached_accounts = Some(parse_value(value, arg)?);
+            }
+
+            flag if flag.starts_with("--") => {
+                return Err(
+                    RunnerError::InvalidArgument(
+                        format!(
+                            "{} is not a valid option",
+                            flag,
+                        ),
+                    ),
+                );
+            }
+
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let base_asset = options.account_config.base_asset;
+
+    for (arg, value) in amount_args.into_iter() {
+        match arg.as_str() {
+            "--credit-limit" => {
+                options.account_config.credit_limit =
+                    parse_amount(value, &options.scales, base_asset, arg)?;
+            }
+
+            "--max-withdrawal" => {
+                options.account_config.withdrawal_rules.max_withdrawal =
+                    Some(parse_amount(value, &options.scales, base_asset, arg)?);
+            }
+
+            "--daily-withdrawal-cap" => {
+                options.account_config.withdrawal_rules.daily_withdrawal_cap =
+                    Some(parse_amount(value, &options.scales, base_asset, arg)?);
+            }
+
+            _ => parse_fee_rule(value, &mut options, arg)?,
+        }
+    }
+
+    Ok((parse_command(&positional)?, options))
+}
@@ -2,32 +2,93 @@ use std::error::Error;
 
 use simledger;
 
+mod args;
+#[cfg(feature = "memory-stats")]
+mod memory;
 mod runner;
 mod macros;
 mod util;
 
+#[cfg(feature = "memory-stats")]
+#[global_allocator]
+static ALLOCATOR: memory::CountingAllocator = memory::CountingAllocator;
+
 #[tokio::main]
 async fn main() {
-    let args = std::env::args();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (command, options) = match args::parse_args(&args) {
+        Err(err) => {
+            if !args.is_empty() {
+                eprintln!("Error: {:?}\n", err);
+            }
+
+            println!(
+                "{} v{} -- insert coin to continue\n",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION"),
+            );
+
+            println!(
+                "Usage: {} ./filepath.csv",
+                env!("CARGO_PKG_NAME"),
+            );
 
-    if args.len() != 2 {
-        println!(
-            "{} v{} -- insert coin to continue\n",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION"),
-        );
+            println!(
+                "       {} balance-at ./filepath.csv <client> <seq>",
+                env!("CARGO_PKG_NAME"),
+            );
 
-        println!(
-            "Usage: {} ./filepath.csv",
-            env!("CARGO_PKG_NAME"),
-        );
+            println!(
+                "       {} statement ./filepath.csv [client]",
+                env!("CARGO_PKG_NAME"),
+            );
 
-        return;
-    }
+            println!(
+                "       {} cases ./filepath.csv [client]",
+                env!("CARGO_PKG_NAME"),
+            );
 
-    let last_arg = args.last().unwrap();
+            println!(
+                "       {} trial-balance ./filepath.csv",
+                env!("CARGO_PKG_NAME"),
+            );
+
+            println!("\nOptions:");
+            println!("       --base-asset <code>               asset of rows without an asset column, defaults to USD");
+            println!("       --dispute-window <seconds>        reject disputes of deposits older than the window");
+            println!("       --max-disputes <count>            limit the number of disputes per deposit");
+            println!("       --unlock-on-representment         unlock accounts once no chargeback is left standing");
+            println!("       --lock-after-chargebacks <count>  lock accounts once this many chargebacks are standing");
+            println!("       --forbid-negative-disputes        reject disputes exceeding the available balance");
+            println!("       --block-on-deficit                reject withdrawals while in deficit");
+            println!("       --credit-limit <amount>           overdraft granted to every client");
+            println!("       --credit-limits <file.csv>        overdrafts of individual clients (client,limit columns)");
+            println!("       --max-withdrawal <amount>         reject withdrawals above the amount");
+            println!("       --daily-withdrawal-cap <amount>   reject withdrawals above the amount per day");
+            println!("       --velocity-transactions <n>/<tx>  allow n withdrawals within the last tx transactions");
+            println!("       --velocity-seconds <n>/<seconds>  allow n withdrawals within the window");
+            println!("       --fee <type>=<rule>               charge fees, e.g. withdrawal=0.5+1% or tiered withdrawal=0.5;100:1%");
+            println!("       --fx-rates <file.csv>             fund withdrawals from the base asset (pair,rate,seq columns)");
+            println!("       --fx-rounding <mode>              round conversions down, up or half-even (default)");
+            println!("       --decimals <code>=<places>        decimal places of the asset, defaults to 4");
+            println!("       --rounding <mode>                 reject, half-even or truncate (default) extra decimal places");
+            println!("       --extended-summary                add held, deficit and credit columns to the summary");
+            println!("       --risk-threshold <score>          flag accounts with a risk score (0-100) this high");
+            println!("       --flagged-report <file.csv>       write the flagged accounts of the summary to the file");
+            println!("       --retention <retention>           compact entries beyond count=<n>, age=<transactions> or the dispute-window");
+            println!("       --memory-stats                    print the peak heap usage and retained entries");
+            println!("       --atomic-batch <count>            apply every count rows all or nothing, rolling back rejected batches");
+            println!("       --spill-dir <dir>                 write accounts beyond the cached ones out to the directory");
+            println!("       --cached-accounts <count>         accounts held in memory with --spill-dir, defaults to 10000");
+
+            return;
+        }
+
+        Ok(parsed) => parsed,
+    };
 
-    match runner::Runner::ignition(last_arg).await {
+    match runner::Runner::ignition(command, options).await {
         Err(err) => eprintln!("Error: {:?}", err),
         Ok(_) => {}
     };
@@ -1,4 +1,5 @@
-use std::convert::{TryFrom, TryInto};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::error::Error;
 
 use csv_async::{AsyncDeserializer, AsyncSerializer};
@@ -8,33 +9,124 @@ use tokio::io::Stdout;
 use tokio_stream::StreamExt;
 
 use simledger::account::Account;
+use simledger::asset::Asset;
+use simledger::dispute::DisputeCase;
+use simledger::execution::{ExecutionError, StoreError};
+use simledger::fx::{FxTable, Rounding};
+use simledger::general_ledger::TrialBalanceRow;
+use simledger::history::{AccountBalance, HistoryEntry};
+use simledger::id::RawClientId;
 use simledger::ledger::Ledger;
+use simledger::policy::ThresholdPolicy;
+use simledger::precision::{AssetScales, InputRounding};
+use simledger::risk::{score_account, RiskConfig, RiskScore};
+use simledger::store::DiskAccountStore;
 use simledger::traits::execution::TransactionExecution;
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::traits::store::AccountStore;
 use simledger::transaction::{Transaction, TransactionTag};
 
+use crate::args::{Command, Options};
+#[cfg(feature = "memory-stats")]
+use crate::memory;
 use crate::unwrap_or_err;
-use crate::util::convert_csv_tx_to_transaction;
+use crate::util::{convert_csv_tx_to_transaction, gl_account_name, transaction_type_name};
 
 #[derive(Debug)]
 pub enum RunnerError {
     InvalidCsvRow,
     FileOpenFailed,
     InvalidColumn(String),
+    InvalidArgument(String),
+    UnknownClient(RawClientId),
+    // the balance at this sequence number was compacted away
+    Compacted(u64),
+    OutputWriteFailed,
     InternalError(&'static str),
 }
 
+// accounts held in memory at once when spilling to disk
+static DEFAULT_CACHED_ACCOUNTS: usize = 10000;
+
+type SelectedAccounts<'a> =
+    Box<dyn Iterator<Item = Result<Cow<'a, Account<ThresholdPolicy>>, StoreError>> + 'a>;
+
 pub struct Runner {
-    pub ledger: Ledger,
+    pub ledger: Ledger<ThresholdPolicy, Box<dyn AccountStore<ThresholdPolicy>>>,
 
     pub csv_reader: AsyncDeserializer<File>,
     pub csv_stdout_writer: AsyncSerializer<Stdout>,
+
+    // number of decimal places per asset and what to do with extra ones
+    pub scales: AssetScales,
+    pub rounding: InputRounding,
+    pub base_asset: Asset,
+
+    // rejected transactions per violated withdrawal rule
+    pub rule_hits: BTreeMap<String, u64>,
+    // lines with amounts beyond the precision of their asset
+    pub rounded_lines: Vec<u64>,
+
+    pub memory_stats: bool,
+    // balance columns beyond the original five and the asset
+    pub extended_summary: bool,
+
+    // rows applied all or nothing, and the first and last line of the
+    // batches rolled back because one of their transactions was rejected
+    pub atomic_batch: Option<usize>,
+    pub rolled_back_batches: Vec<(u64, u64)>,
+}
+
+// rejected transactions are only counted, the run fails
+// once accounts can no longer be written out or read back
+fn record_error(
+    rule_hits: &mut BTreeMap<String, u64>,
+    err: ExecutionError,
+) -> Result<(), RunnerError> {
+    if err == ExecutionError::StorageFailed {
+        return Err(RunnerError::InternalError("accounts could not be written out or read back"));
+    }
+
+    if err.is_rule_violation() {
+        *rule_hits
+            .entry(format!("{:?}", err))
+            .or_insert(0) += 1;
+    }
+
+    Ok(())
 }
 
 impl Runner {
     async fn new(
-        file_name: String,
+        file_name: &str,
+        options: &Options,
     ) -> Result<Runner, RunnerError> {
-        let ledger = Ledger::new();
+        let store: Box<dyn AccountStore<ThresholdPolicy>> =
+            match &options.spill_dir {
+                None => Box::new(BTreeMap::new()),
+                Some(dir) => {
+                    Box::new(
+                        unwrap_or_err!(
+                            DiskAccountStore::new(
+                                dir,
+                                options.cached_accounts.unwrap_or(DEFAULT_CACHED_ACCOUNTS),
+                                options.account_config,
+                                options.policy,
+                            ),
+                            RunnerError::FileOpenFailed
+                        ),
+                    )
+                }
+            };
+
+        let mut ledger =
+            Ledger::with_store(
+                options.account_config,
+                options.policy,
+                store,
+            );
+
+        ledger.set_fee_schedule(options.fee_schedule.clone());
 
         let source_file =
             unwrap_or_err!(
@@ -62,10 +154,165 @@ impl Runner {
 
                 csv_reader,
                 csv_stdout_writer,
+
+                scales: options.scales.clone(),
+                rounding: options.rounding,
+                base_asset: options.account_config.base_asset,
+
+                rule_hits: BTreeMap::new(),
+                rounded_lines: Vec::new(),
+
+                memory_stats: options.memory_stats,
+                extended_summary: options.extended_summary,
+
+                atomic_batch: options.atomic_batch,
+                rolled_back_batches: Vec::new(),
             }
         )
     }
 
+    pub async fn load_credit_limits(&mut self, file_name: &str) -> Result<(), RunnerError> {
+        let source_file =
+            unwrap_or_err!(
+                File::open(file_name).await,
+                RunnerError::FileOpenFailed
+            );
+
+        let mut csv_reader =
+            csv_async
+            ::AsyncDeserializer
+            ::from_reader(
+                source_file,
+            );
+
+        let mut records =
+            csv_reader
+                .deserialize::<CsvCreditLimit>();
+
+        while let Some(record) = records.next().await {
+            let record = unwrap_or_err!(
+                record,
+                RunnerError::InvalidCsvRow
+            );
+
+            let client_id: RawClientId =
+                unwrap_or_err!(
+                    record.client.trim().parse(),
+                    RunnerError::InvalidColumn(
+                        format!(
+                            "{:?} could not be parsed (client)",
+                            record.client,
+                        ),
+                    )
+                );
+
+            // limits are in the base asset
+            let limit =
+                unwrap_or_err!(
+                    self.scales.parse_amount(
+                        self.base_asset,
+                        &record.limit,
+                        InputRounding::Reject,
+                    ),
+                    RunnerError::InvalidColumn(
+                        format!(
+                            "{:?} could not be parsed (limit)",
+                            record.limit,
+                        ),
+                    )
+                );
+
+            self.ledger.set_credit_limit(
+                client_id,
+                limit.amount,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn load_fx_rates(
+        &mut self,
+        file_name: &str,
+        rounding: Rounding,
+    ) -> Result<(), RunnerError> {
+        let source_file =
+            unwrap_or_err!(
+                File::open(file_name).await,
+                RunnerError::FileOpenFailed
+            );
+
+        let mut csv_reader =
+            csv_async
+            ::AsyncDeserializer
+            ::from_reader(
+                source_file,
+            );
+
+        let mut records =
+            csv_reader
+                .deserialize::<CsvFxRate>();
+
+        let mut fx_table = FxTable::new(rounding);
+        fx_table.scales = self.scales.clone();
+
+        while let Some(record) = records.next().await {
+            let record = unwrap_or_err!(
+                record,
+                RunnerError::InvalidCsvRow
+            );
+
+            let invalid_column = |name: &str, value: &str| {
+                RunnerError::InvalidColumn(
+                    format!(
+                        "{:?} could not be parsed ({})",
+                        value,
+                        name,
+                    ),
+                )
+            };
+
+            // <from>/<to>, e.g. EUR/USD
+            let pair: Vec<Option<Asset>> =
+                record.pair
+                    .split('/')
+                    .map(|code| Asset::new(code.trim()))
+                    .collect();
+
+            let (from, to) =
+                match pair.as_slice() {
+                    [Some(from), Some(to)] => (*from, *to),
+                    _ => return Err(invalid_column("pair", &record.pair)),
+                };
+
+            let rate =
+                unwrap_or_err!(
+                    FxTable::parse_rate(&record.rate),
+                    invalid_column("rate", &record.rate)
+                );
+
+            let seq: u64 =
+                unwrap_or_err!(
+                    record.seq.trim().parse(),
+                    invalid_column("seq", &record.seq)
+                );
+
+            unwrap_or_err!(
+                fx_table.add_rate(
+                    from,
+                    to,
+                    rate,
+                    seq,
+                ),
+                invalid_column("rate", &record.rate)
+            );
+        }
+
+        self.ledger.set_fx_table(fx_table);
+
+        Ok(())
+    }
+
     pub async fn process_csv(&mut self) -> Result<(), RunnerError> {
         let mut records =
             self.csv_reader
@@ -74,56 +321,500 @@ impl Runner {
         // first line should be header
         let mut line = 2;
 
+        // transactions of the pending atomic batch and the line it starts on
+        let mut batch: Vec<Transaction> = Vec::new();
+        let mut batch_line = line;
+
         while let Some(record) = records.next().await {
             let record = unwrap_or_err!(
                 record,
                 RunnerError::InvalidCsvRow
             );
 
+            let parsed =
+                convert_csv_tx_to_transaction(
+                    &record,
+                    &self.scales,
+                    self.rounding,
+                    self.base_asset,
+                );
+
+            line += 1;
+
             let tx: Transaction =
-                match record.clone().try_into() {
+                match parsed {
                     Err(err) => {
                         eprintln!(
                             "OUTPUT MAY BE INVALID -- Error while parsing line {}: {:?}",
-                            line,
+                            line - 1,
                             err,
                         );
 
                         continue;
                     },
-                    Ok(tx) => tx,
+                    Ok((tx, rounded)) => {
+                        if rounded {
+                            self.rounded_lines.push(line - 1);
+                        }
+
+                        tx
+                    },
                 };
 
-            self.ledger
-                .execute_transaction(
-                    &tx,
+            let batch_size =
+                match self.atomic_batch {
+                    None => {
+                        if let Err(err) = self.ledger.execute_transaction(&tx) {
+                            record_error(&mut self.rule_hits, err)?;
+                        }
+
+                        continue;
+                    }
+                    Some(batch_size) => batch_size,
+                };
+
+            if batch.is_empty() {
+                batch_line = line - 1;
+            }
+
+            batch.push(tx);
+
+            if batch.len() >= batch_size {
+                if let Err(err) = self.ledger.execute_batch(&batch) {
+                    self.rolled_back_batches.push((batch_line, line - 1));
+
+                    record_error(&mut self.rule_hits, err)?;
+                }
+
+                batch.clear();
+            }
+        }
+
+        // the last batch may be shorter
+        if !batch.is_empty() {
+            if let Err(err) = self.ledger.execute_batch(&batch) {
+                self.rolled_back_batches.push((batch_line, line - 1));
+
+                record_error(&mut self.rule_hits, err)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // takes the writer apart from the runner, so rows can be written
+    // while the accounts of the ledger are being read back
+    async fn write_balance(
+        csv_writer: &mut AsyncSerializer<Stdout>,
+        scales: &AssetScales,
+        extended_summary: bool,
+        client_id: RawClientId,
+        asset: Asset,
+        balance: &AccountBalance,
+    ) -> Result<(), RunnerError> {
+        let written =
+            if extended_summary {
+                csv_writer
+                    .serialize(&CsvExtendedLedgerSummary::from_balance(client_id, asset, balance, scales))
+                    .await
+            } else {
+                csv_writer
+                    .serialize(&CsvLedgerSummary::from_balance(client_id, asset, balance, scales))
+                    .await
+            };
+
+        unwrap_or_err!(
+            written,
+            RunnerError::OutputWriteFailed
+        );
+
+        Ok(())
+    }
+
+    pub async fn write_summary(&mut self) -> Result<(), RunnerError> {
+        for account in Runner::selected_accounts(&self.ledger, None)? {
+            let account = unwrap_or_err!(account, RunnerError::InternalError("accounts could not be read back"));
+
+            // one row per asset held by the client
+            for asset in account.assets() {
+                Runner::write_balance(
+                    &mut self.csv_stdout_writer,
+                    &self.scales,
+                    self.extended_summary,
+                    account.id(),
+                    asset,
+                    &account.balance_in(asset),
+                ).await?;
+            }
+        }
+
+        let total_deficit =
+            unwrap_or_err!(
+                self.ledger.total_deficit(),
+                RunnerError::InternalError("accounts could not be read back")
+            );
+        let total_lost =
+            unwrap_or_err!(
+                self.ledger.total_lost(),
+                RunnerError::InternalError("accounts could not be read back")
+            );
+
+        if total_deficit > 0 || total_lost > 0 {
+            eprintln!(
+                "Ledger deficit: {}, lost to chargebacks: {}",
+                self.scales.format_amount(self.base_asset, total_deficit),
+                self.scales.format_amount(self.base_asset, total_lost),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn flagged_accounts(
+        &self,
+        risk_config: &RiskConfig,
+    ) -> Result<Vec<(RawClientId, RiskScore)>, RunnerError> {
+        let mut flagged = Vec::new();
+
+        for account in Runner::selected_accounts(&self.ledger, None)? {
+            let account = unwrap_or_err!(account, RunnerError::InternalError("accounts could not be read back"));
+            let risk = score_account(&account, risk_config);
+
+            if risk.flagged {
+                flagged.push((account.id(), risk));
+            }
+        }
+
+        Ok(flagged)
+    }
+
+    pub async fn write_flagged_report(
+        &self,
+        risk_config: &RiskConfig,
+        file_name: Option<&str>,
+    ) -> Result<(), RunnerError> {
+        let flagged = self.flagged_accounts(risk_config)?;
+
+        if flagged.is_empty() {
+            return Ok(());
+        }
+
+        let client_ids: Vec<String> =
+            flagged.iter()
+                .map(|(client_id, _)| client_id.to_string())
+                .collect();
+
+        eprintln!("Flagged accounts: {}", client_ids.join(", "));
+
+        let file_name =
+            match file_name {
+                None => return Ok(()),
+                Some(file_name) => file_name,
+            };
+
+        let target_file =
+            unwrap_or_err!(
+                File::create(file_name).await,
+                RunnerError::FileOpenFailed
+            );
+
+        let mut csv_writer =
+            csv_async
+            ::AsyncSerializer
+            ::from_writer(
+                target_file,
+            );
+
+        for (client_id, risk) in flagged.iter() {
+            unwrap_or_err!(
+                csv_writer
+                    .serialize(&CsvFlaggedAccountRow::from_risk_score(*client_id, risk))
+                    .await,
+                RunnerError::OutputWriteFailed
+            );
+        }
+
+        unwrap_or_err!(
+            csv_writer.flush().await,
+            RunnerError::OutputWriteFailed
+        );
+
+        Ok(())
+    }
+
+    pub async fn write_balance_at(
+        &mut self,
+        client_id: RawClientId,
+        seq: u64,
+        asset: Asset,
+    ) -> Result<(), RunnerError> {
+        let account =
+            unwrap_or_err!(
+                self.ledger.account(client_id),
+                RunnerError::InternalError("accounts could not be read back")
+            );
+
+        let balance =
+            match account {
+                None => return Err(RunnerError::UnknownClient(client_id)),
+                Some(account) => {
+                    unwrap_or_err!(
+                        account.balance_at_in(seq, asset),
+                        RunnerError::Compacted(seq)
+                    )
+                }
+            };
+
+        Runner::write_balance(
+            &mut self.csv_stdout_writer,
+            &self.scales,
+            self.extended_summary,
+            client_id,
+            asset,
+            &balance,
+        ).await
+    }
+
+    // accounts spilled to disk are read back one at a time
+    fn selected_accounts(
+        ledger: &Ledger<ThresholdPolicy, Box<dyn AccountStore<ThresholdPolicy>>>,
+        client_id: Option<RawClientId>,
+    ) -> Result<SelectedAccounts<'_>, RunnerError> {
+        let client_id =
+            match client_id {
+                None => return Ok(ledger.store().iter()),
+                Some(client_id) => client_id,
+            };
+
+        let account =
+            unwrap_or_err!(
+                ledger.account(client_id),
+                RunnerError::InternalError("accounts could not be read back")
+            );
+
+        match account {
+            None => Err(RunnerError::UnknownClient(client_id)),
+            Some(account) => Ok(Box::new(std::iter::once(Ok(account)))),
+        }
+    }
+
+    pub async fn write_statement(
+        &mut self,
+        client_id: Option<RawClientId>,
+    ) -> Result<(), RunnerError> {
+        for account in Runner::selected_accounts(&self.ledger, client_id)? {
+            let account = unwrap_or_err!(account, RunnerError::InternalError("accounts could not be read back"));
+
+            for entry in account.history().iter() {
+                let statement_row =
+                    CsvStatementRow::from_history_entry(
+                        account.id(),
+                        entry,
+                        &self.scales,
+                    );
+
+                unwrap_or_err!(
+                    self.csv_stdout_writer
+                        .serialize(&statement_row)
+                        .await,
+                    RunnerError::OutputWriteFailed
                 );
+            }
+        }
 
-            line += 1;
+        Ok(())
+    }
+
+    pub async fn write_cases(
+        &mut self,
+        client_id: Option<RawClientId>,
+    ) -> Result<(), RunnerError> {
+        for account in Runner::selected_accounts(&self.ledger, client_id)? {
+            let account = unwrap_or_err!(account, RunnerError::InternalError("accounts could not be read back"));
+
+            for case in account.dispute_cases().iter() {
+                let case_row =
+                    CsvDisputeCaseRow::from_dispute_case(
+                        account.id(),
+                        case,
+                        &self.scales,
+                    );
+
+                unwrap_or_err!(
+                    self.csv_stdout_writer
+                        .serialize(&case_row)
+                        .await,
+                    RunnerError::OutputWriteFailed
+                );
+            }
         }
 
-        for (_, account) in self.ledger.accounts().iter() {
-            let account_summary: CsvLedgerSummary =
-                account.clone().into();
+        Ok(())
+    }
+
+    pub async fn write_trial_balance(&mut self) -> Result<(), RunnerError> {
+        let general_ledger =
+            match self.ledger.general_ledger() {
+                None => return Err(RunnerError::InternalError("general ledger not enabled")),
+                Some(general_ledger) => general_ledger,
+            };
+
+        let rows = general_ledger.trial_balance();
 
-            self.csv_stdout_writer
-                .serialize(&account_summary)
-                .await;
+        if !general_ledger.is_balanced() {
+            eprintln!("Trial balance does not net to zero");
+        }
+
+        // rows are ordered by asset, each asset closes with its total
+        for (n, row) in rows.iter().enumerate() {
+            unwrap_or_err!(
+                self.csv_stdout_writer
+                    .serialize(&CsvTrialBalanceRow::from_row(row, &self.scales))
+                    .await,
+                RunnerError::OutputWriteFailed
+            );
+
+            if rows.get(n + 1).map_or(false, |next| next.asset == row.asset) {
+                continue;
+            }
+
+            let asset_rows =
+                rows.iter()
+                    .filter(|asset_row| asset_row.asset == row.asset);
+
+            let total_row =
+                CsvTrialBalanceRow {
+                    asset: row.asset.to_string(),
+                    account: String::from("total"),
+                    debit: self.scales.format_amount(row.asset, asset_rows.clone().map(|asset_row| asset_row.debit).sum()),
+                    credit: self.scales.format_amount(row.asset, asset_rows.map(|asset_row| asset_row.credit).sum()),
+                };
+
+            unwrap_or_err!(
+                self.csv_stdout_writer
+                    .serialize(&total_row)
+                    .await,
+                RunnerError::OutputWriteFailed
+            );
         }
 
         Ok(())
     }
 
+    pub fn write_run_report(&self) {
+        if !self.rule_hits.is_empty() {
+            let rule_hits: Vec<String> =
+                self.rule_hits
+                    .iter()
+                    .map(|(rule, hits)| format!("{}: {}", rule, hits))
+                    .collect();
+
+            eprintln!("Rule hits: {}", rule_hits.join(", "));
+        }
+
+        if !self.rounded_lines.is_empty() {
+            let rounded_lines: Vec<String> =
+                self.rounded_lines
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect();
+
+            eprintln!("Rounded amounts on lines: {}", rounded_lines.join(", "));
+        }
+
+        if !self.rolled_back_batches.is_empty() {
+            let batches: Vec<String> =
+                self.rolled_back_batches
+                    .iter()
+                    .map(|(first, last)| format!("{}-{}", first, last))
+                    .collect();
+
+            eprintln!("Rolled back batches on lines: {}", batches.join(", "));
+        }
+
+        if self.memory_stats {
+            // allocations are only counted when built with the memory-stats feature
+            #[cfg(feature = "memory-stats")]
+            eprintln!(
+                "Heap memory: {} KiB peak, {} KiB in use",
+                memory::peak_allocated() / 1024,
+                memory::allocated() / 1024,
+            );
+
+            // the run has already failed if accounts can't be read back
+            if let Ok(stats) = self.ledger.retention_stats() {
+                eprintln!(
+                    "Book entries: {} held, {} compacted, history entries: {} held, {} compacted",
+                    stats.book_entries,
+                    stats.compacted_book_entries,
+                    stats.history_entries,
+                    stats.compacted_history_entries,
+                );
+            }
+        }
+    }
+
     pub async fn ignition(
-        file_name: String,
+        command: Command,
+        options: Options,
     ) -> Result<(), RunnerError> {
         let mut runner =
-            Runner::new(file_name)
+            Runner::new(command.file_name(), &options)
                 .await?;
 
+        if let Command::TrialBalance { .. } = command {
+            runner.ledger.enable_general_ledger();
+        }
+
+        if let Some(file_name) = &options.credit_limits_file {
+            runner
+                .load_credit_limits(file_name)
+                .await?;
+        }
+
+        if let Some(file_name) = &options.fx_rates_file {
+            runner
+                .load_fx_rates(file_name, options.fx_rounding)
+                .await?;
+        }
+
         runner
             .process_csv()
-            .await
+            .await?;
+
+        let result =
+            match command {
+                Command::Summary { .. } => {
+                    runner.write_summary().await?;
+
+                    runner
+                        .write_flagged_report(
+                            &options.risk_config,
+                            options.flagged_report_file.as_deref(),
+                        )
+                        .await
+                }
+
+                Command::BalanceAt { client_id, seq, .. } => {
+                    runner.write_balance_at(client_id, seq, options.account_config.base_asset).await
+                }
+
+                Command::Statement { client_id, .. } => {
+                    runner.write_statement(client_id).await
+                }
+
+                Command::Cases { client_id, .. } => {
+                    runner.write_cases(client_id).await
+                }
+
+                Command::TrialBalance { .. } => {
+                    runner.write_trial_balance().await
+                }
+            };
+
+        runner.write_run_report();
+
+        result
     }
 }
 
@@ -134,44 +825,225 @@ pub struct CsvTransaction {
     pub client: String,
     pub tx: String,
     pub amount: String,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub asset: Option<String>,
+}
+
+// the original five columns come first, new ones are only ever appended
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvLedgerSummary {
+    pub client: String,
+    pub available: String,
+    pub held: String,
+    pub total: String,
+    pub locked: String,
+    pub asset: String,
 }
 
-impl TryInto<Transaction> for CsvTransaction {
-    type Error = RunnerError;
+impl CsvLedgerSummary {
+    pub fn from_balance(
+        client_id: RawClientId,
+        asset: Asset,
+        balance: &AccountBalance,
+        scales: &AssetScales,
+    ) -> CsvLedgerSummary {
+        let format_amount = |amount| scales.format_amount(asset, amount);
 
-    fn try_into(self) -> Result<Transaction, Self::Error> {
-        convert_csv_tx_to_transaction(
-            &self,
-        )
+        CsvLedgerSummary {
+            client: client_id.to_string(),
+            available: format_amount(balance.available),
+            held: format_amount(balance.held),
+            total: format_amount(balance.total),
+            locked: format!("{:?}", balance.locked),
+            asset: asset.to_string(),
+        }
     }
 }
 
+// written with --extended-summary
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct CsvLedgerSummary {
+pub struct CsvExtendedLedgerSummary {
     pub client: String,
     pub available: String,
     pub held: String,
     pub total: String,
     pub locked: String,
+    pub asset: String,
+    pub held_disputed: String,
+    pub held_authorized: String,
+    pub deficit: String,
+    pub lost: String,
+    pub credit_limit: String,
+    pub credit_used: String,
+    pub credit_remaining: String,
 }
 
-impl Into<CsvLedgerSummary> for Account {
-    fn into(self) -> CsvLedgerSummary {
-        let amount_available =
-            self.amount_available() as f32 / 10_000f32;
+impl CsvExtendedLedgerSummary {
+    pub fn from_balance(
+        client_id: RawClientId,
+        asset: Asset,
+        balance: &AccountBalance,
+        scales: &AssetScales,
+    ) -> CsvExtendedLedgerSummary {
+        let format_amount = |amount| scales.format_amount(asset, amount);
 
-        let amount_held =
-            self.amount_held() as f32 / 10_000f32;
+        let CsvLedgerSummary { client, available, held, total, locked, asset: asset_code } =
+            CsvLedgerSummary::from_balance(client_id, asset, balance, scales);
 
-        let amount_total =
-            self.amount_total() as f32 / 10_000f32;
+        CsvExtendedLedgerSummary {
+            client,
+            available,
+            held,
+            total,
+            locked,
+            asset: asset_code,
+            held_disputed: format_amount(balance.held_disputed),
+            held_authorized: format_amount(balance.held_authorized),
+            deficit: format_amount(balance.deficit),
+            lost: format_amount(balance.lost),
+            credit_limit: format_amount(balance.credit_limit),
+            credit_used: format_amount(balance.credit_used),
+            credit_remaining: format_amount(balance.credit_limit - balance.credit_used),
+        }
+    }
+}
 
-        CsvLedgerSummary {
-            client: self.id().to_string(),
-            available: amount_available.to_string(),
-            held: amount_held.to_string(),
-            total: amount_total.to_string(),
-            locked: format!("{:?}", self.locked()),
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvStatementRow {
+    pub client: String,
+    pub seq: String,
+    #[serde(rename = "type")]
+    pub tx_type: String,
+    pub tx: String,
+    pub asset: String,
+    pub amount: String,
+    pub available: String,
+    pub held: String,
+    pub total: String,
+    pub locked: String,
+}
+
+impl CsvStatementRow {
+    pub fn from_history_entry(
+        client_id: RawClientId,
+        entry: &HistoryEntry,
+        scales: &AssetScales,
+    ) -> CsvStatementRow {
+        let format_amount = |amount| scales.format_amount(entry.asset, amount);
+
+        CsvStatementRow {
+            client: client_id.to_string(),
+            seq: entry.seq.to_string(),
+            tx_type: transaction_type_name(&entry.tx.tag).to_string(),
+            tx: entry.tx.id.to_string(),
+            asset: entry.asset.to_string(),
+            amount: format_amount(entry.amount),
+            available: format_amount(entry.balance.available),
+            held: format_amount(entry.balance.held),
+            total: format_amount(entry.balance.total),
+            locked: format!("{:?}", entry.balance.locked),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvDisputeCaseRow {
+    pub client: String,
+    pub case: String,
+    pub tx: String,
+    pub amount: String,
+    pub charged_back: String,
+    pub opened_seq: String,
+    pub closed_seq: String,
+    pub state: String,
+    pub outcome: String,
+    pub prior_disputes: String,
+}
+
+impl CsvDisputeCaseRow {
+    pub fn from_dispute_case(
+        client_id: RawClientId,
+        case: &DisputeCase,
+        scales: &AssetScales,
+    ) -> CsvDisputeCaseRow {
+        let format_amount = |amount| scales.format_amount(case.asset, amount);
+
+        CsvDisputeCaseRow {
+            client: client_id.to_string(),
+            case: case.case_id.to_string(),
+            tx: case.tx_id.to_string(),
+            amount: format_amount(case.amount),
+            charged_back: format_amount(case.charged_back),
+            opened_seq: case.opened_seq.to_string(),
+            closed_seq: case.closed_seq.map(|seq| seq.to_string()).unwrap_or_default(),
+            state: format!("{:?}", case.state),
+            outcome: case.outcome.map(|outcome| format!("{:?}", outcome)).unwrap_or_default(),
+            prior_disputes: case.prior_disputes.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvCreditLimit {
+    pub client: String,
+    pub limit: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvFxRate {
+    pub pair: String,
+    pub rate: String,
+    pub seq: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvTrialBalanceRow {
+    pub asset: String,
+    pub account: String,
+    pub debit: String,
+    pub credit: String,
+}
+
+impl CsvTrialBalanceRow {
+    pub fn from_row(
+        row: &TrialBalanceRow,
+        scales: &AssetScales,
+    ) -> CsvTrialBalanceRow {
+        CsvTrialBalanceRow {
+            asset: row.asset.to_string(),
+            account: gl_account_name(&row.account),
+            debit: scales.format_amount(row.asset, row.debit),
+            credit: scales.format_amount(row.asset, row.credit),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CsvFlaggedAccountRow {
+    pub client: String,
+    pub score: String,
+    pub signals: String,
+}
+
+impl CsvFlaggedAccountRow {
+    pub fn from_risk_score(
+        client_id: RawClientId,
+        risk: &RiskScore,
+    ) -> CsvFlaggedAccountRow {
+        let signals: Vec<String> =
+            risk.signals
+                .iter()
+                .map(|signal| format!("{:?}", signal))
+                .collect();
+
+        CsvFlaggedAccountRow {
+            client: client_id.to_string(),
+            score: risk.score.to_string(),
+            signals: signals.join(" "),
         }
     }
 }
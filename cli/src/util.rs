@@ -1,15 +1,94 @@
+use std::cell::Cell;
+
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::general_ledger::GlAccount;
+use simledger::id::{ClientId, TxId};
+use simledger::precision::{AssetScales, InputRounding};
 use simledger::transaction::{Transaction, TransactionTag};
 
 use crate::runner::{CsvTransaction, RunnerError};
 use crate::unwrap_or_err;
 
+pub fn transaction_type_name(tag: &TransactionTag) -> &'static str {
+    match tag {
+        TransactionTag::Deposit(_) => "deposit",
+        TransactionTag::Withdrawal(_) => "withdrawal",
+
+        TransactionTag::Dispute(_) => "dispute",
+        TransactionTag::Resolve(_) => "resolve",
+        TransactionTag::Chargeback(_) => "chargeback",
+        TransactionTag::Representment => "representment",
+        TransactionTag::SecondChargeback => "second_chargeback",
+
+        TransactionTag::Authorize(_) => "authorize",
+        TransactionTag::Capture => "capture",
+        TransactionTag::Void => "void",
+
+        TransactionTag::Transfer(_, _) => "transfer",
+        TransactionTag::TransferIn(_, _) => "transfer_in",
+        TransactionTag::TransferReversal(_) => "transfer_reversal",
+
+        TransactionTag::Fee(_) => "fee",
+        TransactionTag::ConversionOut(_, _) => "conversion_out",
+        TransactionTag::ConversionIn(_, _) => "conversion_in",
+        TransactionTag::CreditLimit(_) => "credit_limit",
+    }
+}
+
+pub fn gl_account_name(account: &GlAccount) -> String {
+    match account {
+        GlAccount::SettlementCash => String::from("settlement_cash"),
+        GlAccount::TransferClearing => String::from("transfer_clearing"),
+
+        GlAccount::ClientLiabilities(client_id) => format!("client_liabilities:{}", client_id),
+        GlAccount::DisputeReserve => String::from("dispute_reserve"),
+        GlAccount::AuthorizationReserve => String::from("authorization_reserve"),
+
+        GlAccount::ChargebackLosses => String::from("chargeback_losses"),
+        GlAccount::FeeRevenue => String::from("fee_revenue"),
+
+        GlAccount::CurrencyExchange => String::from("currency_exchange"),
+    }
+}
+
+// amounts are read at the precision of the asset of the row, the
+// returned flag tells whether any of them had to be rounded
 pub fn convert_csv_tx_to_transaction(
     csv_tx: &CsvTransaction,
-) -> Result<Transaction, RunnerError> {
-    let amount = || -> Result<i64, RunnerError> {
-        let amnt: f32 =
+    scales: &AssetScales,
+    rounding: InputRounding,
+    base_asset: Asset,
+) -> Result<(Transaction, bool), RunnerError> {
+    // the base asset of the ledger applies if not given
+    let asset: Option<Asset> =
+        match csv_tx.asset.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(code) => match Asset::new(code) {
+                None => {
+                    return Err(
+                        RunnerError::InvalidColumn(
+                            format!(
+                                "{:?} is not a valid asset code (asset)",
+                                code,
+                            ),
+                        ),
+                    );
+                }
+                asset => asset,
+            },
+        };
+
+    let rounded = Cell::new(false);
+
+    let amount = || -> Result<Amount, RunnerError> {
+        let parsed =
             unwrap_or_err!(
-                csv_tx.amount.parse(),
+                scales.parse_amount(
+                    asset.unwrap_or(base_asset),
+                    &csv_tx.amount,
+                    rounding,
+                ),
                 RunnerError::InvalidColumn(
                     format!(
                         "{:?} could not be parsed (amount)",
@@ -18,18 +97,110 @@ pub fn convert_csv_tx_to_transaction(
                 )
             );
 
-        // lock precision at 10'000th of a dollar
-        Ok((amnt * 10_000.0) as i64)
+        if parsed.rounded {
+            rounded.set(true);
+        }
+
+        Ok(parsed.amount)
+    };
+
+    // administrative rows may carry an amount to act on part of a deposit
+    let partial_amount = || -> Result<Option<Amount>, RunnerError> {
+        if csv_tx.amount.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(amount()?))
+        }
+    };
+
+    // rows of these types act on the whole referenced transaction
+    let no_amount = || -> Result<(), RunnerError> {
+        if csv_tx.amount.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(
+                RunnerError::InvalidColumn(
+                    format!(
+                        "{:?} is not expected for {} (amount)",
+                        csv_tx.amount,
+                        csv_tx.tx_type,
+                    ),
+                ),
+            )
+        }
     };
 
-    let tx_tag =
+    // transfers name their recipient in the optional to column
+    let recipient = || -> Result<ClientId, RunnerError> {
+        let to = csv_tx.to.as_deref().unwrap_or("");
+
+        Ok(
+            unwrap_or_err!(
+                to.parse(),
+                RunnerError::InvalidColumn(
+                    format!(
+                        "{:?} could not be parsed (to)",
+                        to,
+                    ),
+                )
+            ),
+        )
+    };
+
+    let client: ClientId =
+        unwrap_or_err!(
+            csv_tx.client.parse(),
+            RunnerError::InvalidColumn(
+                format!(
+                    "{:?} could not be parsed (client)",
+                    csv_tx.client,
+                ),
+            )
+        );
+
+    let tx: TxId =
+        unwrap_or_err!(
+            csv_tx.tx.parse(),
+            RunnerError::InvalidColumn(
+                format!(
+                    "{:?} could not be parsed (tx)",
+                    csv_tx.tx,
+                ),
+            )
+        );
+
+    // amounts are validated by the lib like for every other front end
+    let built =
         match &*csv_tx.tx_type {
-            "deposit" => TransactionTag::Deposit(amount()?),
-            "withdrawal" => TransactionTag::Withdrawal(amount()?),
+            "deposit" => Transaction::deposit(client, tx, amount()?),
+            "withdrawal" => Transaction::withdrawal(client, tx, amount()?),
 
-            "dispute" => TransactionTag::Dispute,
-            "resolve" => TransactionTag::Resolve,
-            "chargeback" => TransactionTag::Chargeback,
+            "dispute" => Transaction::dispute(client, tx, partial_amount()?),
+            "resolve" => Transaction::resolve(client, tx, partial_amount()?),
+            "chargeback" => Transaction::chargeback(client, tx, partial_amount()?),
+            "representment" => {
+                no_amount()?;
+                Transaction::representment(client, tx)
+            }
+            "second_chargeback" => {
+                no_amount()?;
+                Transaction::second_chargeback(client, tx)
+            }
+
+            "authorize" => Transaction::authorize(client, tx, amount()?),
+            "capture" => {
+                no_amount()?;
+                Transaction::capture(client, tx)
+            }
+            "void" => {
+                no_amount()?;
+                Transaction::void(client, tx)
+            }
+
+            "transfer" => Transaction::transfer(client, tx, recipient()?, amount()?),
+            "transfer_reversal" => Transaction::transfer_reversal(client, tx, partial_amount()?),
+
+            "credit_limit" => Transaction::credit_limit(client, tx, amount()?),
 
             val => {
                 return Err(
@@ -43,33 +214,42 @@ pub fn convert_csv_tx_to_transaction(
             }
         };
 
-    let client_id: u16 =
+    let transaction =
         unwrap_or_err!(
-            csv_tx.client.parse(),
+            built,
             RunnerError::InvalidColumn(
                 format!(
-                    "{:?} could not be parsed (client)",
+                    "{:?} is not a valid amount for {} (amount)",
                     csv_tx.amount,
+                    csv_tx.tx_type,
                 ),
             )
         );
 
-    let tx_id: u32 =
-        unwrap_or_err!(
-            csv_tx.tx.parse(),
-            RunnerError::InvalidColumn(
-                format!(
-                    "{:?} could not be parsed (tx)",
-                    csv_tx.amount,
+    let timestamp: Option<u64> =
+        match csv_tx.timestamp.as_deref() {
+            None | Some("") => None,
+            Some(timestamp) => Some(
+                unwrap_or_err!(
+                    timestamp.parse(),
+                    RunnerError::InvalidColumn(
+                        format!(
+                            "{:?} could not be parsed (timestamp)",
+                            timestamp,
+                        ),
+                    )
                 ),
-            )
-        );
+            ),
+        };
 
     Ok(
-        Transaction {
-            id: tx_id,
-            client_id,
-            tag: tx_tag,
-        },
+        (
+            Transaction {
+                timestamp,
+                asset,
+                ..transaction
+            },
+            rounded.get(),
+        ),
     )
 }
@@ -1,131 +1,1328 @@
 use std::collections::BTreeMap;
 
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::codec::Encode;
+use crate::config::{AccountConfig, DisputeWindow};
+use crate::dispute::{DisputeCase, DisputeOutcome, DisputeState};
 use crate::execution::{ExecutionError, ExecutionResult};
-use crate::traits::account::{AccountBookActions, AccountBookEntry, AccountDebitCredit};
-use crate::traits::execution::TransactionExecution;
+use crate::fee::FeeEntry;
+use crate::fx::FxTable;
+use crate::history::{AccountBalance, HistoryEntry};
+use crate::id::{RawClientId, RawTxId};
+use crate::policy::DefaultPolicy;
+use crate::retention::{CompactedEntries, Retention};
+use crate::savepoint::AccountUndo;
+use crate::traits::account::{AccountAuthorizations, AccountBookActions, AccountBookEntry, AccountConversions, AccountDebitCredit, AccountFees, AccountTransfers};
+use crate::traits::execution::{SequencedTransactionExecution, TransactionExecution};
+use crate::traits::policy::AccountPolicy;
 use crate::traits::transaction::{BookEntryExt, TagConstraints};
 use crate::transaction::{LedgerBookEntry, Transaction, TransactionTag};
 
 #[derive(Clone)]
-pub struct Account {
-    id: u16,
+pub struct Account<P: AccountPolicy = DefaultPolicy> {
+    id: RawClientId,
+
+    config: AccountConfig,
+    policy: P,
 
     is_locked: bool,
 
-    amount_available: i64,
+    // per asset, the base asset is always present
+    amounts_available: BTreeMap<Asset, Amount>,
+    // the available balance in the base asset may drop this far below zero
+    credit_limit: Amount,
+
+    pub book: BTreeMap<RawTxId, LedgerBookEntry>,
+    pub book_disputed: BTreeMap<RawTxId, LedgerBookEntry>,
+    pub book_chargeback: BTreeMap<RawTxId, LedgerBookEntry>,
+    // pending authorizations, not yet captured or voided
+    pub book_authorized: BTreeMap<RawTxId, LedgerBookEntry>,
+    // ordered by sequence number, several fees may share a transaction id
+    pub book_fees: Vec<FeeEntry>,
+
+    // ordered by sequence number
+    history: Vec<HistoryEntry>,
+    // transactions rejected for reusing the id of a booked one, and withdrawals
+    // rejected because the funds were held, both survive rollbacks
+    duplicate_attempts: u32,
+    held_withdrawal_attempts: u32,
+
+    // ordered by case id, starting at 1, closed cases may be compacted
+    dispute_cases: Vec<DisputeCase>,
 
-    pub book: BTreeMap<u32, LedgerBookEntry>,
-    pub book_disputed: BTreeMap<u32, LedgerBookEntry>,
-    pub book_chargeback: BTreeMap<u32, LedgerBookEntry>,
+    // what is left of the entries dropped by the retention
+    compacted: CompactedEntries,
+
+    // sequence number of the transaction being applied
+    current_seq: u64,
 }
 
 impl Account {
-    pub fn new(id: u16) -> Account {
+    pub fn new(id: RawClientId) -> Account {
+        Account::with_config(
+            id,
+            AccountConfig::default(),
+        )
+    }
+
+    pub fn with_config(id: RawClientId, config: AccountConfig) -> Account {
+        Account::with_policy(
+            id,
+            config,
+            DefaultPolicy,
+        )
+    }
+}
+
+impl<P: AccountPolicy> Account<P> {
+    pub fn with_policy(id: RawClientId, config: AccountConfig, policy: P) -> Account<P> {
+        let mut amounts_available = BTreeMap::new();
+        amounts_available.insert(config.base_asset, 0);
+
         Account {
             id,
 
+            config,
+            policy,
+
             is_locked: false,
 
-            amount_available: 0,
+            amounts_available,
+            credit_limit: config.credit_limit,
 
             book: BTreeMap::new(),
             book_disputed: BTreeMap::new(),
             book_chargeback: BTreeMap::new(),
+            book_authorized: BTreeMap::new(),
+            book_fees: Vec::new(),
+
+            history: Vec::new(),
+            duplicate_attempts: 0,
+            held_withdrawal_attempts: 0,
+
+            dispute_cases: Vec::new(),
+
+            compacted: CompactedEntries::default(),
+
+            current_seq: 0,
+        }
+    }
+
+    pub fn id(&self) -> RawClientId { self.id }
+    pub fn config(&self) -> &AccountConfig { &self.config }
+    pub fn policy(&self) -> &P { &self.policy }
+    pub fn locked(&self) -> bool { self.is_locked }
+
+    pub fn lock(&mut self) {
+        self.is_locked = true;
+    }
+
+    pub fn unlock(&mut self) {
+        self.is_locked = false;
+    }
+
+    pub fn assert_is_within_dispute_window(
+        &self,
+        subject_tx: &LedgerBookEntry,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if let (
+            DisputeWindow::Seconds(window),
+            Some(deposited_at),
+            Some(disputed_at),
+        ) = (self.config.dispute_window, subject_tx.timestamp, tx.timestamp) {
+            if disputed_at.saturating_sub(deposited_at) > window {
+                return Err(ExecutionError::DisputeWindowExpired);
+            }
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    pub fn assert_is_not_locked(
+        &self,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if self.locked() {
+            Err(ExecutionError::AccountLocked)
+        } else {
+            Ok(ExecutionResult::Ok)
+        }
+    }
+
+    pub fn base_asset(&self) -> Asset {
+        self.config.base_asset
+    }
+
+    // every asset the account ever held, in order
+    pub fn assets(&self) -> Vec<Asset> {
+        self.amounts_available
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    // asset a transaction acts in, administrative transactions
+    // act in the asset of the entry they reference
+    pub fn asset_of(&self, tx: &Transaction) -> Asset {
+        let base_asset = self.config.base_asset;
+
+        match tx.tag {
+            TransactionTag::Deposit(_)
+            | TransactionTag::Withdrawal(_)
+            | TransactionTag::Authorize(_)
+            | TransactionTag::Transfer(_, _)
+            | TransactionTag::TransferIn(_, _)
+            | TransactionTag::Fee(_)
+            | TransactionTag::ConversionOut(_, _)
+            | TransactionTag::ConversionIn(_, _) => tx.asset.unwrap_or(base_asset),
+
+            TransactionTag::CreditLimit(_) => base_asset,
+
+            _ => {
+                self.book.get(&tx.id)
+                    .or_else(|| self.book_disputed.get(&tx.id))
+                    .or_else(|| self.book_chargeback.get(&tx.id))
+                    .or_else(|| self.book_authorized.get(&tx.id))
+                    .map(|entry| entry.asset)
+                    .or(tx.asset)
+                    .unwrap_or(base_asset)
+            }
+        }
+    }
+
+    fn assert_is_in_asset(
+        &self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        match tx.asset {
+            Some(asset) if asset != self.asset_of(tx) => {
+                Err(ExecutionError::InvalidTransaction)
+            }
+
+            // partial amounts without an asset are given in the base asset,
+            // acting on an entry in another asset they would be misread
+            None if tx.tag.partial_amount().is_some()
+                && self.asset_of(tx) != self.config.base_asset => {
+                Err(ExecutionError::InvalidTransaction)
+            }
+
+            _ => Ok(ExecutionResult::Ok),
+        }
+    }
+
+    fn book_entry(&self, tx: &Transaction) -> LedgerBookEntry {
+        LedgerBookEntry {
+            asset: self.asset_of(tx),
+            ..(*tx).into()
+        }
+    }
+
+    // balances only change through these, a change that would overflow
+    // the available balance fails before anything else is touched
+
+    fn add_available(&mut self, asset: Asset, amount: Amount) -> Result<Amount, ExecutionError> {
+        let available =
+            self.amount_available_in(asset)
+                .checked_add(amount)
+                .ok_or(ExecutionError::Overflow)?;
+
+        self.amounts_available.insert(asset, available);
+
+        Ok(available)
+    }
+
+    fn sub_available(&mut self, asset: Asset, amount: Amount) -> Result<Amount, ExecutionError> {
+        let available =
+            self.amount_available_in(asset)
+                .checked_sub(amount)
+                .ok_or(ExecutionError::Overflow)?;
+
+        self.amounts_available.insert(asset, available);
+
+        Ok(available)
+    }
+
+    // amounts without an asset are in the base asset, held and total sums
+    // never saturate as transactions leaving them unrepresentable are
+    // rejected, fee sums saturate instead of wrapping around
+
+    pub fn amount_available(&self) -> Amount {
+        self.amount_available_in(self.config.base_asset)
+    }
+
+    pub fn amount_available_in(&self, asset: Asset) -> Amount {
+        self.amounts_available
+            .get(&asset)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn credit_limit(&self) -> Amount {
+        self.credit_limit
+    }
+
+    fn credit_limit_in(&self, asset: Asset) -> Amount {
+        if asset == self.config.base_asset {
+            self.credit_limit
+        } else {
+            0
+        }
+    }
+
+    // available balance including the unused overdraft
+    pub fn amount_spendable(&self) -> Amount {
+        self.amount_spendable_in(self.config.base_asset)
+    }
+
+    pub fn amount_spendable_in(&self, asset: Asset) -> Amount {
+        self.amount_available_in(asset).saturating_add(self.credit_limit_in(asset))
+    }
+
+    pub fn amount_credit_used(&self) -> Amount {
+        self.amount_available().saturating_neg().max(0).min(self.credit_limit)
+    }
+
+    pub fn amount_credit_remaining(&self) -> Amount {
+        self.credit_limit - self.amount_credit_used()
+    }
+
+    pub fn amount_held(&self) -> Amount {
+        self.amount_held_in(self.config.base_asset)
+    }
+
+    pub fn amount_held_in(&self, asset: Asset) -> Amount {
+        self.amount_held_disputed_in(asset).saturating_add(self.amount_held_authorized_in(asset))
+    }
+
+    pub fn amount_held_disputed(&self) -> Amount {
+        self.amount_held_disputed_in(self.config.base_asset)
+    }
+
+    pub fn amount_held_disputed_in(&self, asset: Asset) -> Amount {
+        let mut amount: Amount = 0;
+
+        for (_, entry) in self.book_disputed.iter() {
+            if entry.asset == asset {
+                amount = amount.saturating_add(entry.disputed);
+            }
+        };
+
+        amount
+    }
+
+    pub fn amount_held_authorized(&self) -> Amount {
+        self.amount_held_authorized_in(self.config.base_asset)
+    }
+
+    pub fn amount_held_authorized_in(&self, asset: Asset) -> Amount {
+        let mut amount: Amount = 0;
+
+        for (_, entry) in self.book_authorized.iter() {
+            if entry.asset == asset {
+                amount = amount.saturating_add(entry.authorized_amount().unwrap_or(0));
+            }
+        };
+
+        amount
+    }
+
+    pub fn amount_fees(&self) -> Amount {
+        self.amount_fees_in(self.config.base_asset)
+    }
+
+    pub fn amount_fees_in(&self, asset: Asset) -> Amount {
+        let compacted = self.compacted.fees.get(&asset).copied().unwrap_or(0);
+
+        self.book_fees
+            .iter()
+            .filter(|entry| entry.asset == asset)
+            .map(|entry| entry.amount)
+            .fold(compacted, Amount::saturating_add)
+    }
+
+    pub fn amount_total(&self) -> Amount {
+        self.amount_total_in(self.config.base_asset)
+    }
+
+    pub fn amount_total_in(&self, asset: Asset) -> Amount {
+        self.amount_available_in(asset).saturating_add(self.amount_held_in(asset))
+    }
+
+    // none if the held or total balance in the asset is not representable
+    fn checked_amount_total_in(&self, asset: Asset) -> Option<Amount> {
+        let disputed =
+            self.book_disputed.values()
+                .filter(|entry| entry.asset == asset)
+                .try_fold(0 as Amount, |amount, entry| amount.checked_add(entry.disputed))?;
+
+        let authorized =
+            self.book_authorized.values()
+                .filter(|entry| entry.asset == asset)
+                .try_fold(0 as Amount, |amount, entry| {
+                    amount.checked_add(entry.authorized_amount().unwrap_or(0))
+                })?;
+
+        self.amount_available_in(asset).checked_add(disputed.checked_add(authorized)?)
+    }
+
+    // owed by the client after disputes or chargebacks drove the
+    // available balance below zero, beyond the approved overdraft
+    pub fn amount_deficit(&self) -> Amount {
+        self.amount_deficit_in(self.config.base_asset)
+    }
+
+    pub fn amount_deficit_in(&self, asset: Asset) -> Amount {
+        self.amount_spendable_in(asset).saturating_neg().max(0)
+    }
+
+    // in deficit in any asset
+    pub fn in_deficit(&self) -> bool {
+        self.amounts_available
+            .keys()
+            .any(|asset| self.amount_deficit_in(*asset) > 0)
+    }
+
+    // part of the deficit caused by standing chargebacks,
+    // the platform already paid these funds back
+    pub fn amount_lost(&self) -> Amount {
+        self.amount_lost_in(self.config.base_asset)
+    }
+
+    pub fn amount_lost_in(&self, asset: Asset) -> Amount {
+        let mut charged_back: Amount = 0;
+
+        for (_, entry) in self.book_chargeback.iter() {
+            if entry.asset == asset {
+                charged_back = charged_back.saturating_add(entry.charged_back);
+            }
+        };
+
+        self.amount_deficit_in(asset).min(charged_back)
+    }
+
+    pub fn balance(&self) -> AccountBalance {
+        self.balance_in(self.config.base_asset)
+    }
+
+    pub fn balance_in(&self, asset: Asset) -> AccountBalance {
+        let amount_available = self.amount_available_in(asset);
+        let amount_held_disputed = self.amount_held_disputed_in(asset);
+        let amount_held_authorized = self.amount_held_authorized_in(asset);
+        let amount_held = amount_held_disputed.saturating_add(amount_held_authorized);
+
+        let credit_limit = self.credit_limit_in(asset);
+
+        AccountBalance {
+            available: amount_available,
+            held: amount_held,
+            total: amount_available.saturating_add(amount_held),
+            locked: self.is_locked,
+
+            held_disputed: amount_held_disputed,
+            held_authorized: amount_held_authorized,
+
+            deficit: self.amount_deficit_in(asset),
+            lost: self.amount_lost_in(asset),
+
+            credit_limit,
+            credit_used: amount_available.saturating_neg().max(0).min(credit_limit),
+        }
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    pub fn duplicate_attempts(&self) -> u32 {
+        self.duplicate_attempts
+    }
+
+    pub fn held_withdrawal_attempts(&self) -> u32 {
+        self.held_withdrawal_attempts
+    }
+
+    // balance right after the last transaction with a
+    // sequence number lower than or equal to seq
+    pub fn balance_at(&self, seq: u64) -> Result<AccountBalance, ExecutionError> {
+        self.balance_at_in(seq, self.config.base_asset)
+    }
+
+    pub fn balance_at_in(
+        &self,
+        seq: u64,
+        asset: Asset,
+    ) -> Result<AccountBalance, ExecutionError> {
+        let applied =
+            self.history
+                .partition_point(|entry| entry.seq <= seq);
+
+        let latest =
+            self.history[..applied]
+                .iter()
+                .rev()
+                .find(|entry| entry.asset == asset);
+
+        if let Some(entry) = latest {
+            return Ok(entry.balance);
+        }
+
+        // before the retained history, balances are only known
+        // as of the last compacted entry
+        match self.compacted.balances.get(&asset) {
+            None => Ok(AccountBalance::empty()),
+            Some((compacted_seq, balance)) if *compacted_seq <= seq => Ok(*balance),
+            Some(_) => Err(ExecutionError::TransactionCompacted),
+        }
+    }
+
+    pub fn compacted(&self) -> &CompactedEntries {
+        &self.compacted
+    }
+
+    // drops the history entries out of retention together with the book
+    // entries and fees of their transactions, the latest entry is always kept
+    pub fn compact(&mut self) {
+        let expired =
+            match self.config.retention {
+                Retention::Unlimited => return,
+
+                Retention::Count(count) => self.history.len().saturating_sub(count),
+
+                Retention::Age(age) => {
+                    let oldest_seq = self.current_seq.saturating_sub(age);
+
+                    self.history.partition_point(|entry| entry.seq <= oldest_seq)
+                }
+
+                Retention::DisputeWindow => {
+                    let latest = self.history.last().and_then(|entry| entry.tx.timestamp);
+
+                    let (window, latest) =
+                        match (self.config.dispute_window, latest) {
+                            (DisputeWindow::Seconds(window), Some(latest)) => (window, latest),
+                            _ => return,
+                        };
+
+                    // timestamps are expected to increase along the history
+                    self.history.partition_point(|entry| {
+                        entry.tx.timestamp
+                            .filter(|timestamp| latest.saturating_sub(*timestamp) > window)
+                            .is_some()
+                    })
+                }
+            };
+
+        // the withdrawal rules always see their whole window
+        let expired =
+            expired
+                .min(self.config.withdrawal_rules.window_start(&self.history))
+                .min(self.history.len().saturating_sub(1));
+
+        // compacting in batches keeps the cost per transaction constant
+        if expired == 0 || expired < self.history.len() / 2 {
+            return;
+        }
+
+        for entry in self.history.drain(..expired) {
+            if self.book.remove(&entry.tx.id).is_some() {
+                self.compacted.insert_id(entry.tx.id);
+                self.compacted.book_entries += 1;
+            }
+
+            if let TransactionTag::Deposit(_) = entry.tx.tag {
+                self.compacted.deposits += 1;
+            }
+
+            self.compacted.balances.insert(entry.asset, (entry.seq, entry.balance));
+        }
+
+        self.compacted.history_entries += expired as u64;
+
+        let oldest_seq = self.history.first().map_or(0, |entry| entry.seq);
+        let fees = self.book_fees.partition_point(|fee| fee.seq < oldest_seq);
+
+        for fee in self.book_fees.drain(..fees) {
+            let amount = self.compacted.fees.entry(fee.asset).or_insert(0);
+
+            *amount = amount.saturating_add(fee.amount);
+        }
+
+        // represented cases may still be charged back a second time
+        let cases = self.dispute_cases.len();
+
+        self.dispute_cases.retain(|case| {
+            case.is_represented()
+                || case.closed_seq.filter(|closed_seq| *closed_seq < oldest_seq).is_none()
+        });
+
+        self.compacted.dispute_cases += (cases - self.dispute_cases.len()) as u64;
+    }
+
+    pub fn dispute_cases(&self) -> &[DisputeCase] {
+        &self.dispute_cases
+    }
+
+    pub fn dispute_case(&self, case_id: u32) -> Option<&DisputeCase> {
+        self.dispute_cases
+            .binary_search_by_key(&case_id, |case| case.case_id)
+            .ok()
+            .map(|n| &self.dispute_cases[n])
+    }
+
+    fn dispute_case_mut(&mut self, case_id: u32) -> Option<&mut DisputeCase> {
+        self.dispute_cases
+            .binary_search_by_key(&case_id, |case| case.case_id)
+            .ok()
+            .map(move |n| &mut self.dispute_cases[n])
+    }
+
+    fn open_dispute_case(
+        &mut self,
+        subject_tx: &LedgerBookEntry,
+        tx_id: RawTxId,
+        amount: Amount,
+    ) -> u32 {
+        // compacted cases keep their ids
+        let case_id =
+            (self.compacted.dispute_cases + self.dispute_cases.len() as u64 + 1) as u32;
+
+        self.dispute_cases.push(
+            DisputeCase {
+                case_id,
+
+                tx_id,
+                asset: subject_tx.asset,
+                amount,
+                charged_back: 0,
+
+                opened_seq: self.current_seq,
+                closed_seq: None,
+
+                state: DisputeState::Open,
+                outcome: None,
+
+                prior_disputes: subject_tx.disputes,
+            },
+        );
+
+        case_id
+    }
+
+    fn close_dispute_case(
+        &mut self,
+        subject_tx: &LedgerBookEntry,
+        outcome: DisputeOutcome,
+    ) {
+        let seq = self.current_seq;
+
+        if let Some(case) = subject_tx.case_id.and_then(|case_id| self.dispute_case_mut(case_id)) {
+            case.close(seq, outcome);
+
+            if outcome == DisputeOutcome::ChargedBack {
+                case.charged_back = subject_tx.charged_back;
+            }
+        }
+    }
+
+    // case of a represented deposit, the only ones eligible for a second chargeback
+    fn represented_dispute_case(
+        &self,
+        subject_tx: &LedgerBookEntry,
+    ) -> Result<DisputeCase, ExecutionError> {
+        match subject_tx.case_id.and_then(|case_id| self.dispute_case(case_id)) {
+            Some(case) if case.is_represented() => Ok(*case),
+            _ => Err(ExecutionError::InvalidTransaction),
+        }
+    }
+
+    pub(crate) fn undo_record(&self, tx: &Transaction) -> AccountUndo {
+        let tx_id = tx.id;
+        let asset = self.asset_of(tx);
+
+        let entry =
+            self.book.get(&tx_id)
+                .or_else(|| self.book_disputed.get(&tx_id))
+                .or_else(|| self.book_chargeback.get(&tx_id));
+
+        AccountUndo {
+            tx_id,
+
+            is_locked: self.is_locked,
+            credit_limit: self.credit_limit,
+
+            asset,
+            amount_available: self.amounts_available.get(&asset).copied(),
+
+            book: self.book.get(&tx_id).copied(),
+            book_disputed: self.book_disputed.get(&tx_id).copied(),
+            book_chargeback: self.book_chargeback.get(&tx_id).copied(),
+            book_authorized: self.book_authorized.get(&tx_id).copied(),
+
+            history_len: self.history.len(),
+            book_fees_len: self.book_fees.len(),
+
+            dispute_cases_len: self.dispute_cases.len(),
+            dispute_case: entry
+                .and_then(|entry| entry.case_id)
+                .and_then(|case_id| self.dispute_case(case_id))
+                .copied(),
+
+            current_seq: self.current_seq,
+        }
+    }
+
+    pub(crate) fn apply_undo(&mut self, undo: AccountUndo) {
+        fn restore(
+            book: &mut BTreeMap<RawTxId, LedgerBookEntry>,
+            tx_id: RawTxId,
+            entry: Option<LedgerBookEntry>,
+        ) {
+            match entry {
+                None => book.remove(&tx_id),
+                Some(entry) => book.insert(tx_id, entry),
+            };
+        }
+
+        self.is_locked = undo.is_locked;
+        self.credit_limit = undo.credit_limit;
+
+        match undo.amount_available {
+            None => self.amounts_available.remove(&undo.asset),
+            Some(amount) => self.amounts_available.insert(undo.asset, amount),
+        };
+
+        restore(&mut self.book, undo.tx_id, undo.book);
+        restore(&mut self.book_disputed, undo.tx_id, undo.book_disputed);
+        restore(&mut self.book_chargeback, undo.tx_id, undo.book_chargeback);
+        restore(&mut self.book_authorized, undo.tx_id, undo.book_authorized);
+
+        self.history.truncate(undo.history_len);
+        self.book_fees.truncate(undo.book_fees_len);
+
+        self.dispute_cases.truncate(undo.dispute_cases_len);
+
+        if let Some(case) = undo.dispute_case {
+            if let Some(current) = self.dispute_case_mut(case.case_id) {
+                *current = case;
+            }
+        }
+
+        self.current_seq = undo.current_seq;
+    }
+
+    // config and policy are shared by the accounts of a ledger and left out,
+    // apart from the credit limit granted when the account was opened
+    pub(crate) fn encode_state(&self, out: &mut Vec<u8>) {
+        self.id.encode(out);
+        self.config.credit_limit.encode(out);
+
+        self.is_locked.encode(out);
+
+        self.amounts_available.encode(out);
+        self.credit_limit.encode(out);
+
+        self.book.encode(out);
+        self.book_disputed.encode(out);
+        self.book_chargeback.encode(out);
+        self.book_authorized.encode(out);
+        self.book_fees.encode(out);
+
+        self.history.encode(out);
+        self.duplicate_attempts.encode(out);
+        self.held_withdrawal_attempts.encode(out);
+
+        self.dispute_cases.encode(out);
+
+        self.compacted.encode(out);
+
+        self.current_seq.encode(out);
+    }
+
+    pub(crate) fn decode_state(
+        mut input: &[u8],
+        config: AccountConfig,
+        policy: P,
+    ) -> Option<Account<P>> {
+        let input = &mut input;
+
+        let id = Encode::decode(input)?;
+        let config = AccountConfig {
+            credit_limit: Encode::decode(input)?,
+            ..config
+        };
+
+        Some(
+            Account {
+                id,
+
+                config,
+                policy,
+
+                is_locked: Encode::decode(input)?,
+
+                amounts_available: Encode::decode(input)?,
+                credit_limit: Encode::decode(input)?,
+
+                book: Encode::decode(input)?,
+                book_disputed: Encode::decode(input)?,
+                book_chargeback: Encode::decode(input)?,
+                book_authorized: Encode::decode(input)?,
+                book_fees: Encode::decode(input)?,
+
+                history: Encode::decode(input)?,
+                duplicate_attempts: Encode::decode(input)?,
+                held_withdrawal_attempts: Encode::decode(input)?,
+
+                dispute_cases: Encode::decode(input)?,
+
+                compacted: Encode::decode(input)?,
+
+                current_seq: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl<P: AccountPolicy> AccountDebitCredit for Account<P> {
+    // standalone accounts know no rates, see Account::withdraw
+    fn debit(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        self.withdraw(tx, None)
+    }
+
+    fn credit(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let asset = self.asset_of(tx);
+
+        if self.book.contains_key(&tx.id)
+            || self.book_authorized.contains_key(&tx.id)
+            || self.compacted.contains(tx.id) {
+            return Err(
+                ExecutionError::TransactionExists,
+            );
+        }
+
+        if self.book_chargeback.contains_key(&tx.id)
+            || self.book_disputed.contains_key(&tx.id) {
+            return Err(
+                ExecutionError::TransactionDisputed,
+            );
+        }
+
+        self.assert_is_not_locked()?;
+        self.policy.assert_is_active(self, tx)?;
+
+        if let TransactionTag::Deposit(amount) = tx.tag {
+            self.add_available(asset, amount)?;
+
+            let entry = self.book_entry(tx);
+
+            self.book
+                .insert(
+                    tx.id,
+                    entry,
+                );
+
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
+    }
+
+    // administrative, lowering the limit below the used
+    // overdraft leaves the account in deficit
+    fn set_credit_limit(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if let TransactionTag::CreditLimit(limit) = tx.tag {
+            if limit < 0 {
+                return Err(ExecutionError::InvalidAmount);
+            }
+
+            self.credit_limit = limit;
+
+            Ok(ExecutionResult::Ok)
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
+    }
+}
+
+impl<P: AccountPolicy> AccountBookEntry for Account<P> {
+    fn find_book_entry(
+        &self,
+        tx: &Transaction,
+    ) -> Result<&LedgerBookEntry, ExecutionError> {
+        match self.book.get(&tx.id) {
+            None if self.compacted.contains(tx.id) => Err(ExecutionError::TransactionCompacted),
+            None => Err(ExecutionError::InvalidTransaction),
+            Some(tx) => Ok(tx),
+        }
+    }
+
+    fn find_disputed_book_entry(
+        &self,
+        tx: &Transaction,
+    ) -> Result<&LedgerBookEntry, ExecutionError> {
+        match self.book_disputed.get(&tx.id) {
+            None => Err(ExecutionError::InvalidTransaction),
+            Some(tx) => Ok(tx),
+        }
+    }
+
+    fn find_chargeback_book_entry(
+        &self,
+        tx: &Transaction,
+    ) -> Result<&LedgerBookEntry, ExecutionError> {
+        match self.book_chargeback.get(&tx.id) {
+            None => Err(ExecutionError::InvalidTransaction),
+            Some(tx) => Ok(tx),
+        }
+    }
+
+    fn find_authorized_book_entry(
+        &self,
+        tx: &Transaction,
+    ) -> Result<&LedgerBookEntry, ExecutionError> {
+        match self.book_authorized.get(&tx.id) {
+            None => Err(ExecutionError::InvalidTransaction),
+            Some(tx) => Ok(tx),
+        }
+    }
+}
+
+// amount requested by a partial administrative transaction,
+// defaulting to everything that is left to act upon
+fn requested_amount(
+    tx: &Transaction,
+    max_amount: Amount,
+) -> Result<Amount, ExecutionError> {
+    match tx.tag.partial_amount() {
+        None => Ok(max_amount),
+        Some(amount) if amount > 0 && amount <= max_amount => Ok(amount),
+        Some(_) => Err(ExecutionError::InvalidAmount),
+    }
+}
+
+impl<P: AccountPolicy> AccountBookActions for Account<P> {
+    fn dispute_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_book_entry(tx)?;
+
+        self.assert_is_within_dispute_window(&subject_tx, tx)?;
+
+        if let Some(max_disputes) = self.config.max_disputes {
+            if subject_tx.disputes >= max_disputes {
+                return Err(ExecutionError::DisputeLimitReached);
+            }
+        }
+
+        let amount =
+            requested_amount(
+                tx,
+                subject_tx.credited_amount()?,
+            )?;
+
+        self.policy.assert_is_disputable(self, &subject_tx, amount)?;
+
+        self.sub_available(subject_tx.asset, amount)?;
+
+        let case_id =
+            self.open_dispute_case(
+                &subject_tx,
+                tx.id,
+                amount,
+            );
+
+        subject_tx.disputed = amount;
+        subject_tx.disputes += 1;
+        subject_tx.case_id = Some(case_id);
+
+        self.book.remove(&tx.id);
+        self.book_disputed.insert(tx.id, subject_tx);
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn resolve_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_disputed_book_entry(tx)?;
+
+        if !subject_tx.is_credit() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        let amount =
+            requested_amount(
+                tx,
+                subject_tx.disputed,
+            )?;
+
+        self.add_available(subject_tx.asset, amount)?;
+
+        subject_tx.disputed -= amount;
+
+        // partially resolved disputes stay open
+        if subject_tx.disputed == 0 {
+            self.close_dispute_case(&subject_tx, DisputeOutcome::Resolved);
+
+            subject_tx.case_id = None;
+
+            self.book_disputed.remove(&tx.id);
+            self.book.insert(tx.id, subject_tx);
+        } else {
+            self.book_disputed.insert(tx.id, subject_tx);
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn chargeback_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_disputed_book_entry(tx)?;
+
+        if !subject_tx.is_credit() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        let amount =
+            requested_amount(
+                tx,
+                subject_tx.disputed,
+            )?;
+
+        // whatever part of the dispute is not charged back is released
+        self.add_available(subject_tx.asset, subject_tx.disputed - amount)?;
+
+        subject_tx.disputed = 0;
+        subject_tx.charged_back = amount;
+
+        self.close_dispute_case(&subject_tx, DisputeOutcome::ChargedBack);
+
+        self.book_disputed.remove(&tx.id);
+        self.book_chargeback.insert(tx.id, subject_tx);
+
+        if self.policy.lock_on_chargeback(self) {
+            self.lock();
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn represent_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_chargeback_book_entry(tx)?;
+
+        if !subject_tx.is_deposit() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        self.add_available(subject_tx.asset, subject_tx.charged_back)?;
+
+        subject_tx.charged_back = 0;
+
+        self.close_dispute_case(&subject_tx, DisputeOutcome::Represented);
+
+        self.book_chargeback.remove(&tx.id);
+        self.book.insert(tx.id, subject_tx);
+
+        if self.config.unlock_on_representment && self.book_chargeback.is_empty() {
+            self.unlock();
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn second_chargeback_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_book_entry(tx)?;
+
+        if !subject_tx.is_deposit() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        let case = self.represented_dispute_case(&subject_tx)?;
+
+        self.sub_available(subject_tx.asset, case.charged_back)?;
+
+        subject_tx.charged_back = case.charged_back;
+
+        self.close_dispute_case(&subject_tx, DisputeOutcome::SecondChargedBack);
+
+        self.book.remove(&tx.id);
+        self.book_chargeback.insert(tx.id, subject_tx);
+
+        if self.policy.lock_on_chargeback(self) {
+            self.lock();
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+}
+
+impl<P: AccountPolicy> AccountTransfers for Account<P> {
+    fn transfer_out(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let asset = self.asset_of(tx);
+
+        if self.book.contains_key(&tx.id)
+            || self.book_authorized.contains_key(&tx.id)
+            || self.compacted.contains(tx.id) {
+            return Err(
+                ExecutionError::TransactionExists,
+            );
+        }
+
+        if self.book_chargeback.contains_key(&tx.id)
+            || self.book_disputed.contains_key(&tx.id) {
+            return Err(
+                ExecutionError::TransactionDisputed,
+            );
+        }
+
+        self.assert_is_not_locked()?;
+        self.policy.assert_is_active(self, tx)?;
+
+        if let TransactionTag::Transfer(_, amount) = tx.tag {
+            if self.amount_spendable_in(asset) < amount {
+                return Err(ExecutionError::InsufficientBalance);
+            }
+
+            self.sub_available(asset, amount)?;
+
+            let entry = self.book_entry(tx);
+
+            self.book
+                .insert(
+                    tx.id,
+                    entry,
+                );
+
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
+    }
+
+    fn transfer_in(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let asset = self.asset_of(tx);
+
+        if self.book.contains_key(&tx.id)
+            || self.book_authorized.contains_key(&tx.id)
+            || self.compacted.contains(tx.id) {
+            return Err(
+                ExecutionError::TransactionExists,
+            );
+        }
+
+        if self.book_chargeback.contains_key(&tx.id)
+            || self.book_disputed.contains_key(&tx.id) {
+            return Err(
+                ExecutionError::TransactionDisputed,
+            );
+        }
+
+        self.assert_is_not_locked()?;
+        self.policy.assert_is_active(self, tx)?;
+
+        if let TransactionTag::TransferIn(_, amount) = tx.tag {
+            self.add_available(asset, amount)?;
+
+            let entry = self.book_entry(tx);
+
+            self.book
+                .insert(
+                    tx.id,
+                    entry,
+                );
+
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
         }
     }
 
-    pub fn id(&self) -> u16 { self.id }
-    pub fn locked(&self) -> bool { self.is_locked }
+    // reverses the leg of a transfer booked on this account,
+    // disputed or charged back transfers can't be reversed
+    fn reverse_transfer(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_book_entry(tx)?;
 
-    pub fn lock(&mut self) {
-        self.is_locked = true;
-    }
+        let remaining = subject_tx.transferred_amount()?;
 
-    pub fn unlock(&mut self) {
-        self.is_locked = true;
-    }
+        if remaining == 0 {
+            return Err(ExecutionError::InvalidTransaction);
+        }
 
-    pub fn assert_is_not_locked(
-        &self,
-    ) -> Result<ExecutionResult, ExecutionError> {
-        if self.locked() {
-            Err(ExecutionError::AccountLocked)
+        let amount =
+            requested_amount(
+                tx,
+                remaining,
+            )?;
+
+        if let TransactionTag::TransferIn(_, _) = subject_tx.tag {
+            if self.amount_available_in(subject_tx.asset) < amount {
+                return Err(ExecutionError::InsufficientBalance);
+            }
+
+            self.sub_available(subject_tx.asset, amount)?;
         } else {
-            Ok(ExecutionResult::Ok)
+            self.add_available(subject_tx.asset, amount)?;
         }
-    }
 
-    pub fn amount_available(&self) -> i64 {
-        self.amount_available
+        subject_tx.reversed += amount;
+
+        self.book.insert(tx.id, subject_tx);
+
+        Ok(
+            ExecutionResult::NewAvailableBalance(
+                self.amount_available_in(subject_tx.asset),
+            )
+        )
     }
+}
 
-    pub fn amount_held(&self) -> i64 {
-        let mut amount = 0i64;
+impl<P: AccountPolicy> AccountFees for Account<P> {
+    // fees are charged regardless of locks and may drive
+    // the available balance negative
+    fn charge_fee(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let asset = self.asset_of(tx);
 
-        for (_, LedgerBookEntry(tag)) in self.book_disputed.iter() {
-            if let TransactionTag::Deposit(tx_amount) = tag {
-                amount += tx_amount;
+        if let TransactionTag::Fee(amount) = tx.tag {
+            if amount < 0 {
+                return Err(ExecutionError::InvalidAmount);
             }
-        };
 
-        amount
-    }
+            self.sub_available(asset, amount)?;
+
+            self.book_fees.push(
+                FeeEntry {
+                    seq: self.current_seq,
+                    tx_id: tx.id,
+
+                    asset,
+                    amount,
+                },
+            );
 
-    pub fn amount_total(&self) -> i64 {
-        self.amount_available + self.amount_held()
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
     }
 }
 
-impl AccountDebitCredit for Account {
-    fn debit(
+// conversions are never booked, the history keeps both legs
+impl<P: AccountPolicy> AccountConversions for Account<P> {
+    fn convert_out(
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError> {
-        self.assert_is_not_locked()?;
-
-        if self.book.contains_key(&tx.id) {
-            return Err(
-                ExecutionError::TransactionExists,
-            );
-        }
+        let asset = self.asset_of(tx);
 
-        if self.book_chargeback.contains_key(&tx.id)
-            || self.book_disputed.contains_key(&tx.id) {
-            return Err(
-                ExecutionError::TransactionDisputed,
-            );
-        }
+        if let TransactionTag::ConversionOut(_, amount) = tx.tag {
+            if amount <= 0 {
+                return Err(ExecutionError::InvalidAmount);
+            }
 
-        if let TransactionTag::Withdrawal(amount) = tx.tag {
-            if self.amount_available < amount {
+            if self.amount_spendable_in(asset) < amount {
                 return Err(ExecutionError::InsufficientBalance);
             }
 
-            self.book
-                .insert(
-                    tx.id,
-                    tx.clone().into(),
-                );
+            self.sub_available(asset, amount)?;
+
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
+    }
+
+    fn convert_in(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let asset = self.asset_of(tx);
+
+        if let TransactionTag::ConversionIn(_, amount) = tx.tag {
+            if amount <= 0 {
+                return Err(ExecutionError::InvalidAmount);
+            }
 
-            self.amount_available -= amount;
+            self.add_available(asset, amount)?;
 
             Ok(
                 ExecutionResult::NewAvailableBalance(
-                    self.amount_available,
+                    self.amount_available_in(asset),
                 )
             )
         } else {
             Err(ExecutionError::InvalidTransactionType)
         }
     }
+}
 
-    fn credit(
+impl<P: AccountPolicy> AccountAuthorizations for Account<P> {
+    fn authorize(
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError> {
-        self.assert_is_not_locked()?;
+        let asset = self.asset_of(tx);
 
-        if self.book.contains_key(&tx.id) {
+        if self.book.contains_key(&tx.id)
+            || self.book_authorized.contains_key(&tx.id)
+            || self.compacted.contains(tx.id) {
             return Err(
                 ExecutionError::TransactionExists,
             );
@@ -138,124 +1335,193 @@ impl AccountDebitCredit for Account {
             );
         }
 
-        if let TransactionTag::Deposit(amount) = tx.tag {
-            self.book
+        self.assert_is_not_locked()?;
+        self.policy.assert_is_active(self, tx)?;
+
+        if let TransactionTag::Authorize(amount) = tx.tag {
+            if self.amount_spendable_in(asset) < amount {
+                return Err(ExecutionError::InsufficientBalance);
+            }
+
+            self.sub_available(asset, amount)?;
+
+            let entry = self.book_entry(tx);
+
+            self.book_authorized
                 .insert(
                     tx.id,
-                    tx.clone().into(),
+                    entry,
                 );
 
-            self.amount_available += amount;
-
             Ok(
                 ExecutionResult::NewAvailableBalance(
-                    self.amount_available,
+                    self.amount_available_in(asset),
                 )
             )
         } else {
             Err(ExecutionError::InvalidTransactionType)
         }
     }
-}
 
-impl AccountBookEntry for Account {
-    fn find_book_entry(
-        &self,
+    // settling reserved funds is not affected by a lock
+    fn capture_authorization(
+        &mut self,
         tx: &Transaction,
-    ) -> Result<&LedgerBookEntry, ExecutionError> {
-        match self.book.get(&tx.id) {
-            None => Err(ExecutionError::InvalidTransaction),
-            Some(tx) => Ok(tx),
-        }
-    }
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut subject_tx =
+            *self.find_authorized_book_entry(tx)?;
 
-    fn find_disputed_book_entry(
-        &self,
-        tx: &Transaction,
-    ) -> Result<&LedgerBookEntry, ExecutionError> {
-        match self.book_disputed.get(&tx.id) {
-            None => Err(ExecutionError::InvalidTransaction),
-            Some(tx) => Ok(tx),
-        }
-    }
+        subject_tx.tag =
+            TransactionTag::Withdrawal(
+                subject_tx.authorized_amount()?,
+            );
 
-    fn find_chargeback_book_entry(
-        &self,
-        tx: &Transaction,
-    ) -> Result<&LedgerBookEntry, ExecutionError> {
-        match self.book_chargeback.get(&tx.id) {
-            None => Err(ExecutionError::InvalidTransaction),
-            Some(tx) => Ok(tx),
-        }
+        self.book_authorized.remove(&tx.id);
+        self.book.insert(tx.id, subject_tx);
+
+        Ok(ExecutionResult::Ok)
     }
-}
 
-impl AccountBookActions for Account {
-    fn dispute_book_entry(
+    fn void_authorization(
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError> {
         let subject_tx =
-            self.find_book_entry(&tx)?
-                .clone();
-
-        if !subject_tx.is_deposit() {
-            return Err(ExecutionError::InvalidTransactionType);
-        }
+            *self.find_authorized_book_entry(tx)?;
 
-        self.amount_available -= subject_tx.deposit_amount()?;
+        self.add_available(subject_tx.asset, subject_tx.authorized_amount()?)?;
 
-        self.book.remove(&tx.id);
-        self.book_disputed.insert(tx.id, subject_tx);
+        self.book_authorized.remove(&tx.id);
 
-        Ok(ExecutionResult::Ok)
+        Ok(
+            ExecutionResult::NewAvailableBalance(
+                self.amount_available_in(subject_tx.asset),
+            )
+        )
     }
+}
 
-    fn resolve_book_entry(
-        &mut self,
+impl<P: AccountPolicy> Account<P> {
+    // amount a transaction is going to move, only meaningful
+    // if the transaction is applied successfully afterwards
+    fn moved_amount(
+        &self,
         tx: &Transaction,
-    ) -> Result<ExecutionResult, ExecutionError> {
-        let subject_tx =
-            self.find_disputed_book_entry(&tx)?
-                .clone();
+    ) -> Amount {
+        let max_amount = match tx.tag {
+            TransactionTag::Deposit(amount)
+            | TransactionTag::Withdrawal(amount)
+            | TransactionTag::Authorize(amount)
+            | TransactionTag::Transfer(_, amount)
+            | TransactionTag::TransferIn(_, amount)
+            | TransactionTag::Fee(amount)
+            | TransactionTag::ConversionOut(_, amount)
+            | TransactionTag::ConversionIn(_, amount)
+            | TransactionTag::CreditLimit(amount) => return amount,
 
-        if !subject_tx.is_deposit() {
-            return Err(ExecutionError::InvalidTransactionType);
-        }
+            TransactionTag::Dispute(_) => {
+                self.book.get(&tx.id)
+                    .and_then(|entry| entry.credited_amount().ok())
+            }
 
-        self.amount_available += subject_tx.deposit_amount()?;
+            TransactionTag::Resolve(_)
+            | TransactionTag::Chargeback(_) => {
+                self.book_disputed.get(&tx.id)
+                    .map(|entry| entry.disputed)
+            }
 
-        self.book_disputed.remove(&tx.id);
-        self.book.insert(tx.id, subject_tx);
+            TransactionTag::Representment => {
+                self.book_chargeback.get(&tx.id)
+                    .map(|entry| entry.charged_back)
+            }
 
-        Ok(ExecutionResult::Ok)
+            TransactionTag::SecondChargeback => {
+                self.book.get(&tx.id)
+                    .and_then(|entry| self.represented_dispute_case(entry).ok())
+                    .map(|case| case.charged_back)
+            }
+
+            TransactionTag::Capture
+            | TransactionTag::Void => {
+                self.book_authorized.get(&tx.id)
+                    .and_then(|entry| entry.authorized_amount().ok())
+            }
+
+            TransactionTag::TransferReversal(_) => {
+                self.book.get(&tx.id)
+                    .and_then(|entry| entry.transferred_amount().ok())
+            }
+        };
+
+        match tx.tag.partial_amount() {
+            Some(amount) => amount,
+            None => max_amount.unwrap_or(0),
+        }
     }
 
-    fn chargeback_book_entry(
+    // withdrawals in other assets than the base asset are
+    // checked against the amount limits at the given rates
+    fn withdraw(
         &mut self,
         tx: &Transaction,
+        fx_table: Option<&FxTable>,
     ) -> Result<ExecutionResult, ExecutionError> {
-        let subject_tx =
-            self.find_disputed_book_entry(&tx)?
-                .clone();
+        let asset = self.asset_of(tx);
 
-        if !subject_tx.is_deposit() {
-            return Err(ExecutionError::InvalidTransactionType);
+        if self.book.contains_key(&tx.id)
+            || self.book_authorized.contains_key(&tx.id)
+            || self.compacted.contains(tx.id) {
+            return Err(
+                ExecutionError::TransactionExists,
+            );
         }
 
-        self.book_disputed.remove(&tx.id);
-        self.book_chargeback.insert(tx.id, subject_tx);
+        if self.book_chargeback.contains_key(&tx.id)
+            || self.book_disputed.contains_key(&tx.id) {
+            return Err(
+                ExecutionError::TransactionDisputed,
+            );
+        }
 
-        self.lock();
+        self.assert_is_not_locked()?;
+        self.policy.assert_is_active(self, tx)?;
+        self.config.withdrawal_rules.evaluate(
+            &self.history,
+            tx,
+            self.current_seq,
+            self.config.base_asset,
+            fx_table,
+        )?;
 
-        Ok(ExecutionResult::Ok)
+        if let TransactionTag::Withdrawal(amount) = tx.tag {
+            if self.amount_spendable_in(asset) < amount {
+                return Err(ExecutionError::InsufficientBalance);
+            }
+
+            self.sub_available(asset, amount)?;
+
+            let entry = self.book_entry(tx);
+
+            self.book
+                .insert(
+                    tx.id,
+                    entry,
+                );
+
+            Ok(
+                ExecutionResult::NewAvailableBalance(
+                    self.amount_available_in(asset),
+                )
+            )
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
     }
-}
 
-impl TransactionExecution for Account {
-    fn execute_transaction(
+    fn apply_transaction(
         &mut self,
         tx: &Transaction,
+        fx_table: Option<&FxTable>,
     ) -> Result<ExecutionResult, ExecutionError> {
         match tx.tag {
             // balance flow
@@ -265,22 +1531,173 @@ impl TransactionExecution for Account {
             }
 
             TransactionTag::Withdrawal(_) => {
-                Ok(self.debit(tx)?)
+                Ok(self.withdraw(tx, fx_table)?)
             }
 
             // administrative
 
-            TransactionTag::Dispute => {
+            TransactionTag::Dispute(_) => {
                 Ok(self.dispute_book_entry(tx)?)
             }
 
-            TransactionTag::Resolve => {
+            TransactionTag::Resolve(_) => {
                 Ok(self.resolve_book_entry(tx)?)
             }
 
-            TransactionTag::Chargeback => {
+            TransactionTag::Chargeback(_) => {
                 Ok(self.chargeback_book_entry(tx)?)
             }
+
+            TransactionTag::Representment => {
+                Ok(self.represent_book_entry(tx)?)
+            }
+
+            TransactionTag::SecondChargeback => {
+                Ok(self.second_chargeback_book_entry(tx)?)
+            }
+
+            // card flow
+
+            TransactionTag::Authorize(_) => {
+                Ok(self.authorize(tx)?)
+            }
+
+            TransactionTag::Capture => {
+                Ok(self.capture_authorization(tx)?)
+            }
+
+            TransactionTag::Void => {
+                Ok(self.void_authorization(tx)?)
+            }
+
+            // transfers
+
+            TransactionTag::Transfer(_, _) => {
+                Ok(self.transfer_out(tx)?)
+            }
+
+            TransactionTag::TransferIn(_, _) => {
+                Ok(self.transfer_in(tx)?)
+            }
+
+            TransactionTag::TransferReversal(_) => {
+                Ok(self.reverse_transfer(tx)?)
+            }
+
+            TransactionTag::Fee(_) => {
+                Ok(self.charge_fee(tx)?)
+            }
+
+            TransactionTag::ConversionOut(_, _) => {
+                Ok(self.convert_out(tx)?)
+            }
+
+            TransactionTag::ConversionIn(_, _) => {
+                Ok(self.convert_in(tx)?)
+            }
+
+            TransactionTag::CreditLimit(_) => {
+                Ok(self.set_credit_limit(tx)?)
+            }
+        }
+    }
+}
+
+impl<P: AccountPolicy> Account<P> {
+    // also applies the legs the ledger books on behalf of other transactions
+    pub(crate) fn execute_leg(
+        &mut self,
+        seq: u64,
+        tx: &Transaction,
+        fx_table: Option<&FxTable>,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        self.current_seq = seq;
+
+        self.assert_is_in_asset(tx)?;
+        tx.tag.assert_is_valid()?;
+
+        let asset = self.asset_of(tx);
+        let amount = self.moved_amount(tx);
+
+        let undo = self.undo_record(tx);
+
+        let result =
+            match self.apply_transaction(tx, fx_table) {
+                // reusing the id of a disputed or charged back entry as well
+                Err(err @ ExecutionError::TransactionExists)
+                | Err(err @ ExecutionError::TransactionDisputed) => {
+                    self.duplicate_attempts = self.duplicate_attempts.saturating_add(1);
+
+                    return Err(err);
+                }
+
+                // withdrawing funds held by a dispute
+                Err(err @ ExecutionError::InsufficientBalance) => {
+                    if let TransactionTag::Withdrawal(amount) = tx.tag {
+                        let held = self.amount_held_disputed_in(asset);
+                        let spendable = self.amount_spendable_in(asset);
+
+                        if held > 0 && amount <= spendable.saturating_add(held) {
+                            self.held_withdrawal_attempts =
+                                self.held_withdrawal_attempts.saturating_add(1);
+                        }
+                    }
+
+                    return Err(err);
+                }
+
+                result => result?,
+            };
+
+        // every balance change is checked, but held and total balances sum
+        // up several entries and could still exceed what an amount holds
+        if self.checked_amount_total_in(asset).is_none() {
+            self.apply_undo(undo);
+
+            return Err(ExecutionError::Overflow);
+        }
+
+        self.history.push(
+            HistoryEntry {
+                seq,
+                tx: *tx,
+                asset,
+                amount,
+                balance: self.balance_in(asset),
+            },
+        );
+
+        Ok(result)
+    }
+}
+
+// the legs booked for transfers, fees and conversions have no other
+// side on a standalone account and are only accepted from the ledger
+impl<P: AccountPolicy> SequencedTransactionExecution for Account<P> {
+    fn execute_sequenced_transaction(
+        &mut self,
+        seq: u64,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if tx.tag.is_ledger_leg() {
+            return Err(ExecutionError::InvalidTransactionType);
         }
+
+        self.execute_leg(seq, tx, None)
+    }
+}
+
+impl<P: AccountPolicy> TransactionExecution for Account<P> {
+    fn execute_transaction(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        // standalone accounts number their transactions themselves
+        let seq = match self.history.last() {
+            None => 1,
+            Some(entry) => entry.seq + 1,
+        };
+
+        self.execute_sequenced_transaction(seq, tx)
     }
 }
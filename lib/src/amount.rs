@@ -0,0 +1,18 @@
+// amounts are in the smallest unit of their asset, see precision,
+// ledgers adding up very large amounts may widen them to 128 bits
+#[cfg(not(feature = "wide-amounts"))]
+pub type Amount = i64;
+
+#[cfg(feature = "wide-amounts")]
+pub type Amount = i128;
+
+// for intermediate results that may exceed an amount
+#[cfg(not(feature = "wide-amounts"))]
+pub(crate) fn widen(amount: Amount) -> i128 {
+    i128::from(amount)
+}
+
+#[cfg(feature = "wide-amounts")]
+pub(crate) fn widen(amount: Amount) -> i128 {
+    amount
+}
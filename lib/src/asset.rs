@@ -0,0 +1,61 @@
+use std::fmt;
+
+static MAX_CODE_LEN: usize = 8;
+
+// currency or asset code of up to eight uppercase ascii
+// letters or digits, e.g. USD, EUR or BTC
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Asset {
+    code: [u8; 8],
+}
+
+impl Asset {
+    pub fn new(code: &str) -> Option<Asset> {
+        if code.is_empty() || code.len() > MAX_CODE_LEN {
+            return None;
+        }
+
+        let mut asset = Asset { code: [0; 8] };
+
+        for (n, byte) in code.bytes().enumerate() {
+            if !byte.is_ascii_alphanumeric() {
+                return None;
+            }
+
+            asset.code[n] = byte.to_ascii_uppercase();
+        }
+
+        Some(asset)
+    }
+
+    pub fn code(&self) -> &str {
+        let len =
+            self.code
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(MAX_CODE_LEN);
+
+        // only ever filled with ascii
+        std::str::from_utf8(&self.code[..len]).unwrap_or("")
+    }
+}
+
+impl Default for Asset {
+    fn default() -> Asset {
+        Asset {
+            code: *b"USD\0\0\0\0\0",
+        }
+    }
+}
+
+impl fmt::Debug for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Asset({})", self.code())
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+
+use crate::asset::Asset;
+use crate::dispute::{DisputeCase, DisputeOutcome, DisputeState};
+use crate::fee::FeeEntry;
+use crate::history::{AccountBalance, HistoryEntry};
+use crate::retention::CompactedEntries;
+use crate::transaction::{LedgerBookEntry, Transaction, TransactionTag};
+
+// compact little endian encoding of account state, only ever read back
+// by the build that wrote it, so there is no versioning of the layout
+pub(crate) trait Encode: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+
+    // none if the input is truncated or malformed
+    fn decode(input: &mut &[u8]) -> Option<Self>;
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+
+    Some(bytes)
+}
+
+macro_rules! encode_int {
+    ($($int:ty),*) => {
+        $(
+            impl Encode for $int {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(input: &mut &[u8]) -> Option<$int> {
+                    let mut bytes = [0; std::mem::size_of::<$int>()];
+                    bytes.copy_from_slice(take(input, std::mem::size_of::<$int>())?);
+
+                    Some(<$int>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+encode_int!(u8, u16, u32, u64, i64, i128);
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u8).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<bool> {
+        match u8::decode(input)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => false.encode(out),
+            Some(value) => {
+                true.encode(out);
+                value.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Option<T>> {
+        if bool::decode(input)? {
+            T::decode(input).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+
+        for value in self.iter() {
+            value.encode(out);
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Vec<T>> {
+        let len = u64::decode(input)?;
+
+        (0..len).map(|_| T::decode(input)).collect()
+    }
+}
+
+impl<K: Encode + Ord, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+
+        for (key, value) in self.iter() {
+            key.encode(out);
+            value.encode(out);
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<BTreeMap<K, V>> {
+        let len = u64::decode(input)?;
+
+        (0..len)
+            .map(|_| Some((K::decode(input)?, V::decode(input)?)))
+            .collect()
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<(A, B)> {
+        Some((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+impl Encode for Asset {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.code().len() as u8).encode(out);
+        out.extend_from_slice(self.code().as_bytes());
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Asset> {
+        let len = u8::decode(input)?;
+        let code = std::str::from_utf8(take(input, len as usize)?).ok()?;
+
+        Asset::new(code)
+    }
+}
+
+impl Encode for TransactionTag {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            TransactionTag::Deposit(amount) => {
+                0u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Withdrawal(amount) => {
+                1u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Dispute(amount) => {
+                2u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Resolve(amount) => {
+                3u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Chargeback(amount) => {
+                4u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Representment => 5u8.encode(out),
+
+            TransactionTag::SecondChargeback => 6u8.encode(out),
+
+            TransactionTag::Authorize(amount) => {
+                7u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Capture => 8u8.encode(out),
+
+            TransactionTag::Void => 9u8.encode(out),
+
+            TransactionTag::Transfer(client_id, amount) => {
+                10u8.encode(out);
+                client_id.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::TransferIn(client_id, amount) => {
+                11u8.encode(out);
+                client_id.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::TransferReversal(amount) => {
+                12u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::Fee(amount) => {
+                13u8.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::ConversionOut(asset, amount) => {
+                14u8.encode(out);
+                asset.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::ConversionIn(asset, amount) => {
+                15u8.encode(out);
+                asset.encode(out);
+                amount.encode(out);
+            }
+
+            TransactionTag::CreditLimit(amount) => {
+                16u8.encode(out);
+                amount.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<TransactionTag> {
+        let tag =
+            match u8::decode(input)? {
+                0 => TransactionTag::Deposit(Encode::decode(input)?),
+                1 => TransactionTag::Withdrawal(Encode::decode(input)?),
+                2 => TransactionTag::Dispute(Encode::decode(input)?),
+                3 => TransactionTag::Resolve(Encode::decode(input)?),
+                4 => TransactionTag::Chargeback(Encode::decode(input)?),
+                5 => TransactionTag::Representment,
+                6 => TransactionTag::SecondChargeback,
+                7 => TransactionTag::Authorize(Encode::decode(input)?),
+                8 => TransactionTag::Capture,
+                9 => TransactionTag::Void,
+                10 => TransactionTag::Transfer(Encode::decode(input)?, Encode::decode(input)?),
+                11 => TransactionTag::TransferIn(Encode::decode(input)?, Encode::decode(input)?),
+                12 => TransactionTag::TransferReversal(Encode::decode(input)?),
+                13 => TransactionTag::Fee(Encode::decode(input)?),
+                14 => TransactionTag::ConversionOut(Encode::decode(input)?, Encode::decode(input)?),
+                15 => TransactionTag::ConversionIn(Encode::decode(input)?, Encode::decode(input)?),
+                16 => TransactionTag::CreditLimit(Encode::decode(input)?),
+                _ => return None,
+            };
+
+        Some(tag)
+    }
+}
+
+impl Encode for Transaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.id.encode(out);
+        self.client_id.encode(out);
+        self.tag.encode(out);
+        self.timestamp.encode(out);
+        self.asset.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Transaction> {
+        Some(
+            Transaction {
+                id: Encode::decode(input)?,
+                client_id: Encode::decode(input)?,
+                tag: Encode::decode(input)?,
+                timestamp: Encode::decode(input)?,
+                asset: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for LedgerBookEntry {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.tag.encode(out);
+        self.timestamp.encode(out);
+        self.asset.encode(out);
+        self.disputed.encode(out);
+        self.charged_back.encode(out);
+        self.disputes.encode(out);
+        self.case_id.encode(out);
+        self.reversed.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<LedgerBookEntry> {
+        Some(
+            LedgerBookEntry {
+                tag: Encode::decode(input)?,
+                timestamp: Encode::decode(input)?,
+                asset: Encode::decode(input)?,
+                disputed: Encode::decode(input)?,
+                charged_back: Encode::decode(input)?,
+                disputes: Encode::decode(input)?,
+                case_id: Encode::decode(input)?,
+                reversed: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for FeeEntry {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.seq.encode(out);
+        self.tx_id.encode(out);
+        self.asset.encode(out);
+        self.amount.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<FeeEntry> {
+        Some(
+            FeeEntry {
+                seq: Encode::decode(input)?,
+                tx_id: Encode::decode(input)?,
+                asset: Encode::decode(input)?,
+                amount: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for AccountBalance {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.available.encode(out);
+        self.held.encode(out);
+        self.total.encode(out);
+        self.locked.encode(out);
+        self.held_disputed.encode(out);
+        self.held_authorized.encode(out);
+        self.deficit.encode(out);
+        self.lost.encode(out);
+        self.credit_limit.encode(out);
+        self.credit_used.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<AccountBalance> {
+        Some(
+            AccountBalance {
+                available: Encode::decode(input)?,
+                held: Encode::decode(input)?,
+                total: Encode::decode(input)?,
+                locked: Encode::decode(input)?,
+                held_disputed: Encode::decode(input)?,
+                held_authorized: Encode::decode(input)?,
+                deficit: Encode::decode(input)?,
+                lost: Encode::decode(input)?,
+                credit_limit: Encode::decode(input)?,
+                credit_used: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for HistoryEntry {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.seq.encode(out);
+        self.tx.encode(out);
+        self.asset.encode(out);
+        self.amount.encode(out);
+        self.balance.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<HistoryEntry> {
+        Some(
+            HistoryEntry {
+                seq: Encode::decode(input)?,
+                tx: Encode::decode(input)?,
+                asset: Encode::decode(input)?,
+                amount: Encode::decode(input)?,
+                balance: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for DisputeState {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self == DisputeState::Closed).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<DisputeState> {
+        if bool::decode(input)? {
+            Some(DisputeState::Closed)
+        } else {
+            Some(DisputeState::Open)
+        }
+    }
+}
+
+impl Encode for DisputeOutcome {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let outcome: u8 =
+            match self {
+                DisputeOutcome::Resolved => 0,
+                DisputeOutcome::ChargedBack => 1,
+                DisputeOutcome::Represented => 2,
+                DisputeOutcome::SecondChargedBack => 3,
+            };
+
+        outcome.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<DisputeOutcome> {
+        match u8::decode(input)? {
+            0 => Some(DisputeOutcome::Resolved),
+            1 => Some(DisputeOutcome::ChargedBack),
+            2 => Some(DisputeOutcome::Represented),
+            3 => Some(DisputeOutcome::SecondChargedBack),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for DisputeCase {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.case_id.encode(out);
+        self.tx_id.encode(out);
+        self.asset.encode(out);
+        self.amount.encode(out);
+        self.charged_back.encode(out);
+        self.opened_seq.encode(out);
+        self.closed_seq.encode(out);
+        self.state.encode(out);
+        self.outcome.encode(out);
+        self.prior_disputes.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<DisputeCase> {
+        Some(
+            DisputeCase {
+                case_id: Encode::decode(input)?,
+                tx_id: Encode::decode(input)?,
+                asset: Encode::decode(input)?,
+                amount: Encode::decode(input)?,
+                charged_back: Encode::decode(input)?,
+                opened_seq: Encode::decode(input)?,
+                closed_seq: Encode::decode(input)?,
+                state: Encode::decode(input)?,
+                outcome: Encode::decode(input)?,
+                prior_disputes: Encode::decode(input)?,
+            },
+        )
+    }
+}
+
+impl Encode for CompactedEntries {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.history_entries.encode(out);
+        self.book_entries.encode(out);
+        self.deposits.encode(out);
+        self.dispute_cases.encode(out);
+        self.fees.encode(out);
+        self.balances.encode(out);
+        self.ids.encode(out);
+        self.ids_floor.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<CompactedEntries> {
+        Some(
+            CompactedEntries {
+                history_entries: Encode::decode(input)?,
+                book_entries: Encode::decode(input)?,
+                deposits: Encode::decode(input)?,
+                dispute_cases: Encode::decode(input)?,
+                fees: Encode::decode(input)?,
+                balances: Encode::decode(input)?,
+                ids: Encode::decode(input)?,
+                ids_floor: Encode::decode(input)?,
+            },
+        )
+    }
+}
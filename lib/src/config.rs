@@ -0,0 +1,55 @@
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::retention::Retention;
+use crate::rules::WithdrawalRules;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DisputeWindow {
+    Unlimited,
+
+    // maximum number of seconds between deposit and dispute,
+    // only enforced if both transactions carry a timestamp
+    Seconds(u64),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AccountConfig {
+    // asset of transactions not naming one, overdrafts only apply to it
+    pub base_asset: Asset,
+
+    pub dispute_window: DisputeWindow,
+
+    // maximum number of disputes opened against the same deposit
+    pub max_disputes: Option<u32>,
+
+    // unlock the account once no chargeback is left standing
+    pub unlock_on_representment: bool,
+
+    // overdraft granted to new accounts
+    pub credit_limit: Amount,
+
+    pub withdrawal_rules: WithdrawalRules,
+
+    // applied by the ledger whenever no savepoint is open
+    pub retention: Retention,
+}
+
+impl Default for AccountConfig {
+    fn default() -> AccountConfig {
+        AccountConfig {
+            base_asset: Asset::default(),
+
+            dispute_window: DisputeWindow::Unlimited,
+
+            max_disputes: None,
+
+            unlock_on_representment: false,
+
+            credit_limit: 0,
+
+            withdrawal_rules: WithdrawalRules::default(),
+
+            retention: Retention::default(),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::id::RawTxId;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DisputeState {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DisputeOutcome {
+    Resolved,
+    ChargedBack,
+    Represented,
+    SecondChargedBack,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DisputeCase {
+    pub case_id: u32,
+
+    // disputed deposit
+    pub tx_id: RawTxId,
+    pub asset: Asset,
+    pub amount: Amount,
+    pub charged_back: Amount,
+
+    // sequence numbers of the transactions opening and closing the case
+    pub opened_seq: u64,
+    pub closed_seq: Option<u64>,
+
+    pub state: DisputeState,
+    pub outcome: Option<DisputeOutcome>,
+
+    // disputes opened against the same deposit before this one
+    pub prior_disputes: u32,
+}
+
+impl DisputeCase {
+    pub fn is_represented(&self) -> bool {
+        self.outcome == Some(DisputeOutcome::Represented)
+    }
+
+    pub fn close(
+        &mut self,
+        seq: u64,
+        outcome: DisputeOutcome,
+    ) {
+        self.closed_seq = Some(seq);
+        self.state = DisputeState::Closed;
+        self.outcome = Some(outcome);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::amount::Amount;
 use crate::transaction::{LedgerBookEntry, Transaction};
 
 #[derive(Debug, Eq, PartialEq)]
@@ -6,13 +7,59 @@ pub enum ExecutionError {
     InvalidTransactionType,
     InvalidTransaction,
     TransactionExists,
+    // the referenced transaction was compacted away by the retention
+    TransactionCompacted,
     TransactionDisputed,
     AccountLocked,
+    InvalidSavepoint,
+    DisputeWindowExpired,
+    InvalidAmount,
+    DisputeLimitReached,
+    DisputeExceedsAvailable,
+    AccountInDeficit,
+    // the resulting balance would not be representable
+    Overflow,
+    // the account store failed to write an account out or read it back
+    StorageFailed,
+    // an amount in the base asset had no rate to convert it at
+    RateUnavailable,
+
+    // withdrawal rule violations
+    WithdrawalLimitExceeded,
+    VelocityLimitExceeded,
+    VelocityWindowExceeded,
+    DailyCapExceeded,
+}
+
+impl ExecutionError {
+    pub fn is_rule_violation(&self) -> bool {
+        matches!(
+            self,
+            ExecutionError::WithdrawalLimitExceeded
+            | ExecutionError::VelocityLimitExceeded
+            | ExecutionError::VelocityWindowExceeded
+            | ExecutionError::DailyCapExceeded
+        )
+    }
+}
+
+// failures of an account store, the ledger passes them on as StorageFailed
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StoreError {
+    WriteFailed,
+    // an account written out earlier is gone or no longer decodes
+    ReadFailed,
+}
+
+impl From<StoreError> for ExecutionError {
+    fn from(_: StoreError) -> ExecutionError {
+        ExecutionError::StorageFailed
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ExecutionResult {
     Ok,
     BookEntry(LedgerBookEntry),
-    NewAvailableBalance(i64),
+    NewAvailableBalance(Amount),
 }
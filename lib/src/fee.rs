@@ -0,0 +1,131 @@
+use std::convert::TryFrom;
+
+use crate::amount::{widen, Amount};
+use crate::asset::Asset;
+use crate::execution::ExecutionError;
+use crate::id::RawTxId;
+use crate::precision::{AssetScales, InputRounding};
+use crate::transaction::TransactionTag;
+
+// percentages are kept in basis points, hundredths of a percent
+pub static PERCENTAGE_DECIMALS: u32 = 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Fee {
+    pub flat: Amount,
+    // hundredths of a percent of the amount moved
+    pub basis_points: i64,
+}
+
+impl Fee {
+    pub fn flat(amount: Amount) -> Fee {
+        Fee {
+            flat: amount,
+            basis_points: 0,
+        }
+    }
+
+    pub fn percentage(basis_points: i64) -> Fee {
+        Fee {
+            flat: 0,
+            basis_points,
+        }
+    }
+
+    // reads a percentage like 0.25 exactly, percentages
+    // finer than a basis point can't be charged
+    pub fn parse_basis_points(text: &str) -> Result<i64, ExecutionError> {
+        let mut scales = AssetScales::default();
+        scales.default_decimals = PERCENTAGE_DECIMALS;
+
+        let percentage =
+            scales.parse_amount(
+                Asset::default(),
+                text,
+                InputRounding::Reject,
+            )?;
+
+        i64::try_from(widen(percentage.amount))
+            .map_err(|_| ExecutionError::InvalidAmount)
+    }
+
+    // saturates for amounts too large to charge a fee on
+    pub fn amount_for(&self, amount: Amount) -> Amount {
+        let percentage =
+            widen(amount)
+                .checked_mul(i128::from(self.basis_points))
+                .and_then(|percentage| Amount::try_from(percentage / 10_000).ok());
+
+        match percentage {
+            None => Amount::MAX,
+            Some(percentage) => self.flat.saturating_add(percentage),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FeeRule {
+    Fixed(Fee),
+
+    // lower bound of the amount moved and the fee charged from there on,
+    // the tier with the highest bound not exceeding the amount applies
+    Tiered(Vec<(Amount, Fee)>),
+}
+
+impl FeeRule {
+    // percentages alone apply to amounts in any asset alike
+    pub fn is_percentage_only(&self) -> bool {
+        matches!(self, FeeRule::Fixed(Fee { flat: 0, .. }))
+    }
+
+    pub fn amount_for(&self, amount: Amount) -> Amount {
+        match self {
+            FeeRule::Fixed(fee) => fee.amount_for(amount),
+
+            FeeRule::Tiered(tiers) => {
+                tiers.iter()
+                    .filter(|(lower_bound, _)| *lower_bound <= amount)
+                    .max_by_key(|(lower_bound, _)| *lower_bound)
+                    .map_or(0, |(_, fee)| fee.amount_for(amount))
+            }
+        }
+    }
+}
+
+// fee rules per transaction type, transactions without a rule are free
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FeeSchedule {
+    pub deposit: Option<FeeRule>,
+    pub withdrawal: Option<FeeRule>,
+    pub transfer: Option<FeeRule>,
+    pub capture: Option<FeeRule>,
+
+    // charged for chargebacks and second chargebacks alike
+    pub chargeback: Option<FeeRule>,
+}
+
+impl FeeSchedule {
+    pub fn rule(&self, tag: &TransactionTag) -> Option<&FeeRule> {
+        match tag {
+            TransactionTag::Deposit(_) => self.deposit.as_ref(),
+            TransactionTag::Withdrawal(_) => self.withdrawal.as_ref(),
+            TransactionTag::Transfer(_, _) => self.transfer.as_ref(),
+            TransactionTag::Capture => self.capture.as_ref(),
+
+            TransactionTag::Chargeback(_)
+            | TransactionTag::SecondChargeback => self.chargeback.as_ref(),
+
+            _ => None,
+        }
+    }
+}
+
+// fee charged to an account, booked apart from the transactions it was charged for
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FeeEntry {
+    pub seq: u64,
+    pub tx_id: RawTxId,
+
+    pub asset: Asset,
+    pub amount: Amount,
+}
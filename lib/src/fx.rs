@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::amount::{widen, Amount};
+use crate::asset::Asset;
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::precision::{AssetScales, InputRounding};
+
+// rates are in hundred millionths of the quote asset per unit of the base asset
+pub static RATE_SCALE: i64 = 100_000_000;
+pub static RATE_DECIMALS: u32 = 8;
+
+// applied to converted amounts that don't fit the amount precision
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+    // to the nearest amount, ties to the even one
+    #[default]
+    HalfEven,
+}
+
+impl Rounding {
+    // numerator and denominator must not be negative
+    fn divide(&self, numerator: i128, denominator: i128) -> i128 {
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+
+        if remainder == 0 {
+            return quotient;
+        }
+
+        match self {
+            Rounding::Down => quotient,
+            Rounding::Up => quotient + 1,
+
+            Rounding::HalfEven => {
+                match (remainder * 2).cmp(&denominator) {
+                    std::cmp::Ordering::Less => quotient,
+                    std::cmp::Ordering::Greater => quotient + 1,
+                    std::cmp::Ordering::Equal => quotient + quotient % 2,
+                }
+            }
+        }
+    }
+}
+
+// exchange rates per asset pair, each taking effect from the
+// transaction with the given ledger sequence number onwards
+#[derive(Debug, Clone, Default)]
+pub struct FxTable {
+    pub rounding: Rounding,
+    // rates are per whole unit, converted amounts are scaled accordingly
+    pub scales: AssetScales,
+
+    // ordered by effective sequence number
+    rates: BTreeMap<(Asset, Asset), Vec<(u64, i64)>>,
+}
+
+impl FxTable {
+    // reads a rate like 1.08 exactly, rates finer
+    // than a hundred millionth can't be applied
+    pub fn parse_rate(text: &str) -> Result<i64, ExecutionError> {
+        let mut scales = AssetScales::default();
+        scales.default_decimals = RATE_DECIMALS;
+
+        let rate =
+            scales.parse_amount(
+                Asset::default(),
+                text,
+                InputRounding::Reject,
+            )?;
+
+        i64::try_from(widen(rate.amount))
+            .map_err(|_| ExecutionError::InvalidAmount)
+    }
+
+    pub fn new(rounding: Rounding) -> FxTable {
+        FxTable {
+            rounding,
+            scales: AssetScales::default(),
+
+            rates: BTreeMap::new(),
+        }
+    }
+
+    // rate of one unit of from in to, a later rate with the same
+    // effective sequence number replaces the earlier one
+    pub fn add_rate(
+        &mut self,
+        from: Asset,
+        to: Asset,
+        rate: i64,
+        effective_seq: u64,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if rate <= 0 || from == to {
+            return Err(ExecutionError::InvalidAmount);
+        }
+
+        let rates =
+            self.rates
+                .entry((from, to))
+                .or_default();
+
+        match rates.binary_search_by_key(&effective_seq, |(seq, _)| *seq) {
+            Ok(n) => rates[n].1 = rate,
+            Err(n) => rates.insert(n, (effective_seq, rate)),
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    // rate in effect for the transaction with the given sequence number
+    pub fn rate(
+        &self,
+        from: Asset,
+        to: Asset,
+        seq: u64,
+    ) -> Option<i64> {
+        let rates = self.rates.get(&(from, to))?;
+
+        let effective =
+            rates.partition_point(|(effective_seq, _)| *effective_seq <= seq);
+
+        match effective {
+            0 => None,
+            n => Some(rates[n - 1].1),
+        }
+    }
+
+    // falls back to the inverse of the opposite pair, amounts must not be negative
+    pub fn convert(
+        &self,
+        amount: Amount,
+        from: Asset,
+        to: Asset,
+        seq: u64,
+    ) -> Option<Amount> {
+        if from == to {
+            return Some(amount);
+        }
+
+        let (numerator, denominator) =
+            match (self.rate(from, to, seq), self.rate(to, from, seq)) {
+                (Some(rate), _) => (rate as i128, RATE_SCALE as i128),
+                (None, Some(rate)) => (RATE_SCALE as i128, rate as i128),
+                (None, None) => return None,
+            };
+
+        let numerator =
+            widen(amount)
+                .checked_mul(numerator)?
+                .checked_mul(self.scales.scale(to) as i128)?;
+
+        let denominator = denominator * self.scales.scale(from) as i128;
+
+        let converted = self.rounding.divide(numerator, denominator);
+
+        Amount::try_from(converted).ok()
+    }
+}
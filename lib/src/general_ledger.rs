@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::history::AccountBalance;
+use crate::id::{RawClientId, RawTxId};
+use crate::transaction::Transaction;
+
+// chart of accounts, cash is an asset, everything owed to
+// clients or reserved on their behalf is a liability
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum GlAccount {
+    SettlementCash,
+    // in-flight legs of transfers between clients, always
+    // nets to zero once both legs have been applied
+    TransferClearing,
+
+    // available funds of a single client
+    ClientLiabilities(RawClientId),
+    DisputeReserve,
+    AuthorizationReserve,
+
+    // deficits written off after a chargeback
+    ChargebackLosses,
+    FeeRevenue,
+
+    // position of the platform in an asset after converting client funds
+    CurrencyExchange,
+}
+
+// positive amounts are debits, negative amounts credits,
+// postings only ever balance against those of the same asset
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Posting {
+    pub seq: u64,
+    pub tx_id: RawTxId,
+
+    pub asset: Asset,
+
+    pub account: GlAccount,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TrialBalanceRow {
+    pub asset: Asset,
+    pub account: GlAccount,
+
+    pub debit: Amount,
+    pub credit: Amount,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GeneralLedger {
+    postings: Vec<Posting>,
+}
+
+impl GeneralLedger {
+    pub fn new() -> GeneralLedger {
+        GeneralLedger {
+            postings: Vec::new(),
+        }
+    }
+
+    pub fn postings(&self) -> &[Posting] {
+        &self.postings
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.postings.truncate(len);
+    }
+
+    // books the change of a client balance caused by a transaction,
+    // funds entering or leaving the client are taken from the counter account
+    pub(crate) fn post_balance_change(
+        &mut self,
+        seq: u64,
+        tx: &Transaction,
+        asset: Asset,
+        counter_account: GlAccount,
+        before: &AccountBalance,
+        after: &AccountBalance,
+    ) {
+        let available = after.available.saturating_sub(before.available);
+        let held_disputed = after.held_disputed.saturating_sub(before.held_disputed);
+        let held_authorized = after.held_authorized.saturating_sub(before.held_authorized);
+        let lost = after.lost.saturating_sub(before.lost);
+
+        let changes = [
+            (counter_account, available + held_disputed + held_authorized),
+            (GlAccount::ClientLiabilities(tx.client_id), -(available + lost)),
+            (GlAccount::DisputeReserve, -held_disputed),
+            (GlAccount::AuthorizationReserve, -held_authorized),
+            (GlAccount::ChargebackLosses, lost),
+        ];
+
+        for &(account, amount) in changes.iter() {
+            if amount != 0 {
+                self.postings.push(
+                    Posting {
+                        seq,
+                        tx_id: tx.id,
+
+                        asset,
+                        account,
+                        amount,
+                    },
+                );
+            }
+        }
+    }
+
+    // net balance of every account touched so far, per asset
+    pub fn trial_balance(&self) -> Vec<TrialBalanceRow> {
+        let mut balances: BTreeMap<(Asset, GlAccount), Amount> = BTreeMap::new();
+
+        for posting in self.postings.iter() {
+            let balance = balances.entry((posting.asset, posting.account)).or_insert(0);
+
+            *balance = balance.saturating_add(posting.amount);
+        }
+
+        balances
+            .into_iter()
+            .map(|((asset, account), amount)| {
+                TrialBalanceRow {
+                    asset,
+                    account,
+
+                    debit: amount.max(0),
+                    credit: amount.saturating_neg().max(0),
+                }
+            })
+            .collect()
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        let mut balances: BTreeMap<Asset, Amount> = BTreeMap::new();
+
+        for posting in self.postings.iter() {
+            let balance = balances.entry(posting.asset).or_insert(0);
+
+            *balance = balance.saturating_add(posting.amount);
+        }
+
+        balances
+            .values()
+            .all(|amount| *amount == 0)
+    }
+}
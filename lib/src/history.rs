@@ -0,0 +1,57 @@
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::transaction::Transaction;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AccountBalance {
+    pub available: Amount,
+    pub held: Amount,
+    pub total: Amount,
+    pub locked: bool,
+
+    // portions of held
+    pub held_disputed: Amount,
+    pub held_authorized: Amount,
+
+    pub deficit: Amount,
+    pub lost: Amount,
+
+    pub credit_limit: Amount,
+    pub credit_used: Amount,
+}
+
+impl AccountBalance {
+    pub fn empty() -> AccountBalance {
+        AccountBalance {
+            available: 0,
+            held: 0,
+            total: 0,
+            locked: false,
+
+            held_disputed: 0,
+            held_authorized: 0,
+
+            deficit: 0,
+            lost: 0,
+
+            credit_limit: 0,
+            credit_used: 0,
+        }
+    }
+}
+
+// a successfully applied transaction together with the
+// balance of the account right after its application
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HistoryEntry {
+    pub seq: u64,
+    pub tx: Transaction,
+    // asset the transaction acted in, the balance is the one held in it
+    pub asset: Asset,
+
+    // amount moved by the transaction, for administrative transactions
+    // this is the portion of the referenced deposit acted upon
+    pub amount: Amount,
+
+    pub balance: AccountBalance,
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+// raw ids as stored in transactions and books, ledgers outgrowing
+// 65,536 clients or 4 billion transactions may widen both to 64 bits
+#[cfg(not(feature = "wide-ids"))]
+pub type RawClientId = u16;
+#[cfg(not(feature = "wide-ids"))]
+pub type RawTxId = u32;
+
+#[cfg(feature = "wide-ids")]
+pub type RawClientId = u64;
+#[cfg(feature = "wide-ids")]
+pub type RawTxId = u64;
+
+// typed counterparts to the raw ids of a transaction, keeping
+// client and transaction ids from being swapped by accident
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ClientId(pub RawClientId);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TxId(pub RawTxId);
+
+impl From<RawClientId> for ClientId {
+    fn from(id: RawClientId) -> ClientId {
+        ClientId(id)
+    }
+}
+
+impl From<RawTxId> for TxId {
+    fn from(id: RawTxId) -> TxId {
+        TxId(id)
+    }
+}
+
+impl FromStr for ClientId {
+    type Err = ParseIntError;
+
+    fn from_str(value: &str) -> Result<ClientId, ParseIntError> {
+        Ok(ClientId(value.trim().parse()?))
+    }
+}
+
+impl FromStr for TxId {
+    type Err = ParseIntError;
+
+    fn from_str(value: &str) -> Result<TxId, ParseIntError> {
+        Ok(TxId(value.trim().parse()?))
+    }
+}
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for TxId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -1,38 +1,751 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::iter;
 
 use crate::account::Account;
+use crate::amount::Amount;
+use crate::config::AccountConfig;
 use crate::execution::{ExecutionError, ExecutionResult};
+use crate::fee::FeeSchedule;
+use crate::fx::FxTable;
+use crate::general_ledger::{GeneralLedger, GlAccount};
+use crate::history::AccountBalance;
+use crate::id::{RawClientId, RawTxId};
+use crate::policy::DefaultPolicy;
+use crate::retention::{Retention, RetentionStats};
+use crate::savepoint::{JournalEntry, Savepoint};
 use crate::traits::execution::TransactionExecution;
+use crate::traits::policy::AccountPolicy;
+use crate::traits::savepoint::LedgerSavepoints;
+use crate::traits::store::AccountStore;
 use crate::transaction::{LedgerBookEntry, Transaction, TransactionTag};
 
-pub struct Ledger {
-    accounts: BTreeMap<u16, Account>,
+// accounts are kept in memory unless the ledger is given another store
+pub struct Ledger<P: AccountPolicy = DefaultPolicy, S: AccountStore<P> = BTreeMap<RawClientId, Account<P>>> {
+    accounts: S,
+
+    // applied to every account opened by the ledger
+    account_config: AccountConfig,
+    policy: P,
+
+    fee_schedule: FeeSchedule,
+
+    // withdrawals short of funds are topped up from the base asset, if set
+    fx_table: Option<FxTable>,
+
+    // overdrafts of individual clients, taking precedence over the account config
+    credit_limits: BTreeMap<RawClientId, Amount>,
+
+    // number of transactions submitted to the ledger so far
+    sequence: u64,
+
+    // undo records are only kept while at least one savepoint is open
+    journal: Vec<JournalEntry>,
+    savepoints: Vec<Savepoint>,
+    savepoints_issued: u64,
+
+    // double-entry postings of every applied transaction, if enabled
+    general_ledger: Option<GeneralLedger>,
 }
 
 impl Ledger {
     pub fn new() -> Ledger {
+        Ledger::with_config(
+            AccountConfig::default(),
+        )
+    }
+
+    pub fn with_config(account_config: AccountConfig) -> Ledger {
+        Ledger::with_policy(
+            account_config,
+            DefaultPolicy,
+        )
+    }
+}
+
+impl<P: AccountPolicy> Ledger<P> {
+    pub fn with_policy(account_config: AccountConfig, policy: P) -> Ledger<P> {
+        Ledger::with_store(
+            account_config,
+            policy,
+            BTreeMap::new(),
+        )
+    }
+
+    pub fn accounts(&self) -> &BTreeMap<RawClientId, Account<P>> {
+        &self.accounts
+    }
+}
+
+impl<P: AccountPolicy, S: AccountStore<P>> Ledger<P, S> {
+    pub fn with_store(account_config: AccountConfig, policy: P, accounts: S) -> Ledger<P, S> {
         Ledger {
-            accounts: BTreeMap::new(),
+            accounts,
+
+            account_config,
+            policy,
+
+            fee_schedule: FeeSchedule::default(),
+
+            fx_table: None,
+
+            credit_limits: BTreeMap::new(),
+
+            sequence: 0,
+
+            journal: Vec::new(),
+            savepoints: Vec::new(),
+            savepoints_issued: 0,
+
+            general_ledger: None,
         }
     }
 
-    pub fn accounts(&self) -> &BTreeMap<u16, Account> {
+    // only applies to transactions executed afterwards
+    pub fn set_fee_schedule(&mut self, fee_schedule: FeeSchedule) {
+        self.fee_schedule = fee_schedule;
+    }
+
+    // configures the overdraft of a client up front, clients adjust
+    // it through credit limit transactions afterwards
+    pub fn set_credit_limit(&mut self, client_id: RawClientId, credit_limit: Amount) {
+        self.credit_limits.insert(client_id, credit_limit);
+    }
+
+    pub fn fee_schedule(&self) -> &FeeSchedule {
+        &self.fee_schedule
+    }
+
+    // only applies to transactions executed afterwards
+    pub fn set_fx_table(&mut self, fx_table: FxTable) {
+        self.fx_table = Some(fx_table);
+    }
+
+    pub fn fx_table(&self) -> Option<&FxTable> {
+        self.fx_table.as_ref()
+    }
+
+    pub fn total_fees(&self) -> Result<Amount, ExecutionError> {
+        self.sum_accounts(Account::amount_fees)
+    }
+
+    // only transactions executed afterwards are posted
+    pub fn enable_general_ledger(&mut self) {
+        if self.general_ledger.is_none() {
+            self.general_ledger = Some(GeneralLedger::new());
+        }
+    }
+
+    pub fn general_ledger(&self) -> Option<&GeneralLedger> {
+        self.general_ledger.as_ref()
+    }
+
+    pub fn store(&self) -> &S {
         &self.accounts
     }
+
+    // borrowed unless the store has to read the account back
+    pub fn account(&self, client_id: RawClientId) -> Result<Option<Cow<'_, Account<P>>>, ExecutionError> {
+        Ok(self.accounts.get(client_id)?)
+    }
+
+    // sums up every account, saturating, accounts held on
+    // disk by the store are read back for this
+    fn sum_accounts(
+        &self,
+        amount: impl Fn(&Account<P>) -> Amount,
+    ) -> Result<Amount, ExecutionError> {
+        let mut total: Amount = 0;
+
+        for account in self.accounts.iter() {
+            total = total.saturating_add(amount(&*account?));
+        }
+
+        Ok(total)
+    }
+
+    pub fn retention_stats(&self) -> Result<RetentionStats, ExecutionError> {
+        let mut stats = RetentionStats::default();
+
+        for account in self.accounts.iter() {
+            let account = account?;
+
+            let book_entries =
+                account.book.len()
+                    + account.book_disputed.len()
+                    + account.book_chargeback.len()
+                    + account.book_authorized.len()
+                    + account.book_fees.len();
+
+            stats.book_entries += book_entries as u64;
+            stats.history_entries += account.history().len() as u64;
+
+            stats.compacted_book_entries += account.compacted().book_entries;
+            stats.compacted_history_entries += account.compacted().history_entries;
+        }
+
+        Ok(stats)
+    }
+
+    pub fn total_deficit(&self) -> Result<Amount, ExecutionError> {
+        self.sum_accounts(Account::amount_deficit)
+    }
+
+    pub fn total_lost(&self) -> Result<Amount, ExecutionError> {
+        self.sum_accounts(Account::amount_lost)
+    }
+
+    // sum of all client balances in the base asset,
+    // transfers between clients never change it
+    pub fn amount_total(&self) -> Result<Amount, ExecutionError> {
+        self.sum_accounts(Account::amount_total)
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn balance_at(
+        &self,
+        client_id: RawClientId,
+        seq: u64,
+    ) -> Result<Option<AccountBalance>, ExecutionError> {
+        self.accounts
+            .get(client_id)?
+            .map(|account| account.balance_at(seq))
+            .transpose()
+    }
+
+    fn assert_is_open_savepoint(
+        &self,
+        savepoint: &Savepoint,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        match self.savepoints.get(savepoint.depth) {
+            Some(open) if open == savepoint => Ok(ExecutionResult::Ok),
+
+            _ => Err(ExecutionError::InvalidSavepoint),
+        }
+    }
+
+    fn find_any_book_entry(
+        &self,
+        client_id: RawClientId,
+        tx_id: RawTxId,
+    ) -> Result<Option<LedgerBookEntry>, ExecutionError> {
+        let account =
+            match self.accounts.get(client_id)? {
+                None => return Ok(None),
+                Some(account) => account,
+            };
+
+        Ok(
+            account.book.get(&tx_id)
+                .or_else(|| account.book_disputed.get(&tx_id))
+                .or_else(|| account.book_chargeback.get(&tx_id))
+                .copied(),
+        )
+    }
+
+    // transactions spanning several accounts split into one leg per account,
+    // transfers are always administered through the sender
+    fn transfer_legs(
+        &self,
+        tx: &Transaction,
+    ) -> Result<Option<Vec<Transaction>>, ExecutionError> {
+        if tx.tag.is_ledger_leg() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        match tx.tag {
+            TransactionTag::Transfer(recipient_id, amount) => {
+                if recipient_id == tx.client_id {
+                    return Err(ExecutionError::InvalidTransaction);
+                }
+
+                return Ok(
+                    Some(
+                        vec!(
+                            *tx,
+                            Transaction {
+                                client_id: recipient_id,
+                                tag: TransactionTag::TransferIn(tx.client_id, amount),
+                                ..*tx
+                            },
+                        ),
+                    ),
+                );
+            }
+
+            TransactionTag::Dispute(_)
+            | TransactionTag::Resolve(_)
+            | TransactionTag::Chargeback(_)
+            | TransactionTag::Representment
+            | TransactionTag::SecondChargeback
+            | TransactionTag::TransferReversal(_) => {}
+
+            _ => return Ok(None),
+        }
+
+        let subject_tx =
+            match self.find_any_book_entry(tx.client_id, tx.id)? {
+                None => return Ok(None),
+                Some(entry) => entry,
+            };
+
+        let recipient_id =
+            match subject_tx.tag {
+                TransactionTag::Transfer(recipient_id, _) => recipient_id,
+                TransactionTag::TransferIn(_, _) => {
+                    return Err(ExecutionError::InvalidTransactionType);
+                }
+
+                _ => return Ok(None),
+            };
+
+        let recipient_tx = Transaction {
+            client_id: recipient_id,
+            ..*tx
+        };
+
+        match tx.tag {
+            TransactionTag::TransferReversal(_) => {
+                Ok(Some(vec!(*tx, recipient_tx)))
+            }
+
+            // charged back transfers are returned to the sender
+            TransactionTag::Chargeback(amount) => {
+                let charged_back =
+                    match amount {
+                        None => {
+                            self.find_any_book_entry(recipient_id, tx.id)?
+                                .map(|entry| entry.disputed)
+                        }
+                        amount => amount,
+                    };
+
+                Ok(
+                    Some(
+                        vec!(
+                            recipient_tx,
+                            Transaction {
+                                tag: TransactionTag::TransferReversal(charged_back),
+                                asset: Some(subject_tx.asset),
+                                ..*tx
+                            },
+                        ),
+                    ),
+                )
+            }
+
+            // disputes hold the funds at the recipient
+            _ => Ok(Some(vec!(recipient_tx))),
+        }
+    }
+
+    // converts whatever a withdrawal lacks in its own asset from the
+    // base asset, at the rate in effect for the withdrawal
+    fn conversion_legs(
+        &self,
+        tx: &Transaction,
+    ) -> Result<Vec<Transaction>, ExecutionError> {
+        let (fx_table, amount) =
+            match (&self.fx_table, tx.tag) {
+                (Some(fx_table), TransactionTag::Withdrawal(amount)) => (fx_table, amount),
+                _ => return Ok(Vec::new()),
+            };
+
+        let account =
+            match self.accounts.get(tx.client_id)? {
+                None => return Ok(Vec::new()),
+                Some(account) => account,
+            };
+
+        let asset = account.asset_of(tx);
+        let base_asset = account.base_asset();
+
+        let shortfall = amount.saturating_sub(account.amount_spendable_in(asset));
+
+        if asset == base_asset || shortfall <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let cost =
+            match fx_table.convert(shortfall, asset, base_asset, self.sequence) {
+                None => return Ok(Vec::new()),
+                Some(cost) => cost,
+            };
+
+        Ok(
+            vec!(
+                Transaction {
+                    tag: TransactionTag::ConversionOut(asset, cost),
+                    asset: Some(base_asset),
+                    ..*tx
+                },
+                Transaction {
+                    tag: TransactionTag::ConversionIn(base_asset, shortfall),
+                    asset: Some(asset),
+                    ..*tx
+                },
+            ),
+        )
+    }
+
+    fn execute_leg(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let created = !self.accounts.contains(tx.client_id);
+
+        let account_config = AccountConfig {
+            credit_limit: self.credit_limits
+                .get(&tx.client_id)
+                .copied()
+                .unwrap_or(self.account_config.credit_limit),
+            ..self.account_config
+        };
+
+        let account =
+            if created {
+                self.accounts.insert(
+                    Account::with_policy(
+                        tx.client_id,
+                        account_config,
+                        self.policy.clone(),
+                    ),
+                )?
+            } else {
+                match self.accounts.get_mut(tx.client_id)? {
+                    None => return Err(ExecutionError::StorageFailed),
+                    Some(account) => account,
+                }
+            };
+
+        if !self.savepoints.is_empty() {
+            self.journal.push(
+                JournalEntry {
+                    client_id: tx.client_id,
+                    created,
+                    undo: account.undo_record(tx),
+                },
+            );
+        }
+
+        let general_ledger =
+            match &mut self.general_ledger {
+                None => return account.execute_leg(self.sequence, tx, self.fx_table.as_ref()),
+                Some(general_ledger) => general_ledger,
+            };
+
+        let counter_account = counter_account(account, tx);
+        let asset = account.asset_of(tx);
+        let before = account.balance_in(asset);
+
+        let result = account.execute_leg(self.sequence, tx, self.fx_table.as_ref())?;
+
+        general_ledger.post_balance_change(
+            self.sequence,
+            tx,
+            asset,
+            counter_account,
+            &before,
+            &account.balance_in(asset),
+        );
+
+        Ok(result)
+    }
+
+    // all legs share the sequence number of the submitted transaction
+    // and are applied together with its conversions and fee, or not at all
+    fn execute_legs(
+        &mut self,
+        tx: &Transaction,
+        conversions: &[Transaction],
+        legs: &[Transaction],
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let savepoint = self.savepoint();
+
+        let result = self.execute_legs_with_fee(tx, conversions, legs);
+
+        if result.is_err() {
+            self.rollback_to(savepoint)?;
+        }
+
+        self.release_savepoint(savepoint)?;
+
+        result
+    }
+
+    // the result is the one of the first leg
+    fn execute_legs_with_fee(
+        &mut self,
+        tx: &Transaction,
+        conversions: &[Transaction],
+        legs: &[Transaction],
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let mut result = ExecutionResult::Ok;
+
+        for conversion in conversions.iter() {
+            self.execute_leg(conversion)?;
+        }
+
+        for (n, leg) in legs.iter().enumerate() {
+            let leg_result = self.execute_leg(leg)?;
+
+            if n == 0 {
+                result = leg_result;
+            }
+        }
+
+        if let Some(leg) = legs.first() {
+            self.charge_fee(tx, leg.client_id)?;
+        }
+
+        Ok(result)
+    }
+
+    // undo records only cover the entries touched by a single transaction,
+    // so accounts are only compacted while no savepoint is open
+    fn compact_accounts(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if self.account_config.retention == Retention::Unlimited {
+            return Ok(ExecutionResult::Ok);
+        }
+
+        let recipient_id =
+            match tx.tag {
+                TransactionTag::Transfer(recipient_id, _) => Some(recipient_id),
+                _ => None,
+            };
+
+        for client_id in iter::once(tx.client_id).chain(recipient_id) {
+            if let Some(account) = self.accounts.get_mut(client_id)? {
+                account.compact();
+            }
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    // fees are charged to the account of the first leg, based on the
+    // amount it moved, only chargeback fees may overdraw the account
+    fn charge_fee(
+        &mut self,
+        tx: &Transaction,
+        client_id: RawClientId,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let rule =
+            match self.fee_schedule.rule(&tx.tag) {
+                None => return Ok(ExecutionResult::Ok),
+                Some(rule) => rule,
+            };
+
+        let account =
+            match self.accounts.get(client_id)? {
+                None => return Ok(ExecutionResult::Ok),
+                Some(account) => account,
+            };
+
+        let entry =
+            match account.history().last() {
+                None => return Ok(ExecutionResult::Ok),
+                Some(entry) => *entry,
+            };
+
+        // charged in the asset the transaction acted in, flat fees and tier
+        // bounds are in the base asset so other assets go through the rate
+        let asset = entry.asset;
+        let base_asset = account.base_asset();
+
+        let amount =
+            if asset == base_asset || rule.is_percentage_only() {
+                rule.amount_for(entry.amount)
+            } else {
+                let fx_table =
+                    match &self.fx_table {
+                        None => return Err(ExecutionError::RateUnavailable),
+                        Some(fx_table) => fx_table,
+                    };
+
+                fx_table.convert(entry.amount, asset, base_asset, self.sequence)
+                    .and_then(|moved| {
+                        fx_table.convert(rule.amount_for(moved), base_asset, asset, self.sequence)
+                    })
+                    .ok_or(ExecutionError::RateUnavailable)?
+            };
+
+        if amount <= 0 {
+            return Ok(ExecutionResult::Ok);
+        }
+
+        match tx.tag {
+            TransactionTag::Chargeback(_)
+            | TransactionTag::SecondChargeback => {}
+
+            _ => {
+                if account.amount_spendable_in(asset) < amount {
+                    return Err(ExecutionError::InsufficientBalance);
+                }
+            }
+        }
+
+        self.execute_leg(
+            &Transaction {
+                client_id,
+                tag: TransactionTag::Fee(amount),
+                asset: Some(asset),
+                ..*tx
+            },
+        )
+    }
+}
+
+// funds moved between clients never pass through settlement
+fn counter_account<P: AccountPolicy>(
+    account: &Account<P>,
+    tx: &Transaction,
+) -> GlAccount {
+    match tx.tag {
+        TransactionTag::Transfer(_, _)
+        | TransactionTag::TransferIn(_, _)
+        | TransactionTag::TransferReversal(_) => GlAccount::TransferClearing,
+
+        TransactionTag::Fee(_) => GlAccount::FeeRevenue,
+
+        TransactionTag::ConversionOut(_, _)
+        | TransactionTag::ConversionIn(_, _) => GlAccount::CurrencyExchange,
+
+        TransactionTag::Chargeback(_) => {
+            match account.book_disputed.get(&tx.id).map(|entry| entry.tag) {
+                Some(TransactionTag::TransferIn(_, _)) => GlAccount::TransferClearing,
+                _ => GlAccount::SettlementCash,
+            }
+        }
+
+        _ => GlAccount::SettlementCash,
+    }
 }
 
-impl TransactionExecution for Ledger {
+impl<P: AccountPolicy, S: AccountStore<P>> TransactionExecution for Ledger<P, S> {
     fn execute_transaction(
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError> {
-        self.accounts
-            .entry(tx.client_id)
-            .or_insert(
-                Account::new(
-                    tx.client_id,
-                ),
-            )
-            .execute_transaction(tx)
+        self.sequence += 1;
+
+        let conversions = self.conversion_legs(tx)?;
+
+        let result =
+            match self.transfer_legs(tx)? {
+                None if conversions.is_empty() && self.fee_schedule.rule(&tx.tag).is_none() => {
+                    self.execute_leg(tx)
+                }
+
+                None => self.execute_legs(tx, &conversions, &[*tx]),
+                Some(legs) => self.execute_legs(tx, &conversions, &legs),
+            };
+
+        if self.savepoints.is_empty() {
+            self.compact_accounts(tx)?;
+        }
+
+        result
+    }
+}
+
+impl<P: AccountPolicy, S: AccountStore<P>> LedgerSavepoints for Ledger<P, S> {
+    fn savepoint(
+        &mut self,
+    ) -> Savepoint {
+        self.savepoints_issued += 1;
+
+        let savepoint = Savepoint {
+            id: self.savepoints_issued,
+            depth: self.savepoints.len(),
+            journal_len: self.journal.len(),
+            sequence: self.sequence,
+            postings_len: self.general_ledger
+                .as_ref()
+                .map_or(0, |general_ledger| general_ledger.postings().len()),
+        };
+
+        self.savepoints.push(savepoint);
+
+        savepoint
+    }
+
+    fn rollback_to(
+        &mut self,
+        savepoint: Savepoint,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        self.assert_is_open_savepoint(&savepoint)?;
+
+        while self.journal.len() > savepoint.journal_len {
+            let entry = match self.journal.pop() {
+                None => break,
+                Some(entry) => entry,
+            };
+
+            if entry.created {
+                self.accounts.remove(entry.client_id)?;
+            } else if let Some(account) = self.accounts.get_mut(entry.client_id)? {
+                account.apply_undo(entry.undo);
+            }
+        }
+
+        self.sequence = savepoint.sequence;
+
+        if let Some(general_ledger) = &mut self.general_ledger {
+            general_ledger.truncate(savepoint.postings_len);
+        }
+
+        // the savepoint itself stays open, nested ones are discarded
+        self.savepoints.truncate(savepoint.depth + 1);
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn release_savepoint(
+        &mut self,
+        savepoint: Savepoint,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        self.assert_is_open_savepoint(&savepoint)?;
+
+        self.savepoints.truncate(savepoint.depth);
+
+        if self.savepoints.is_empty() {
+            self.journal.clear();
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn execute_batch(
+        &mut self,
+        txs: &[Transaction],
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let savepoint = self.savepoint();
+
+        for tx in txs.iter() {
+            if let Err(err) = self.execute_transaction(tx) {
+                self.rollback_to(savepoint)?;
+                self.release_savepoint(savepoint)?;
+
+                return Err(err);
+            }
+        }
+
+        self.release_savepoint(savepoint)?;
+
+        // accounts touched by the batch are compacted once it is applied
+        if self.savepoints.is_empty() {
+            for tx in txs.iter() {
+                self.compact_accounts(tx)?;
+            }
+        }
+
+        Ok(ExecutionResult::Ok)
     }
 }
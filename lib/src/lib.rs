@@ -1,6 +1,24 @@
 pub mod ledger;
 pub mod account;
+pub mod amount;
 pub mod transaction;
+pub mod asset;
+pub mod history;
+pub mod id;
+pub mod dispute;
+pub mod policy;
+pub mod savepoint;
+pub mod store;
+pub mod general_ledger;
+pub mod fee;
+pub mod fx;
+pub mod precision;
+pub mod retention;
+pub mod rules;
+pub mod risk;
 
+mod codec;
+
+pub mod config;
 pub mod execution;
 pub mod traits;
@@ -0,0 +1,96 @@
+use crate::account::Account;
+use crate::amount::Amount;
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::traits::policy::AccountPolicy;
+use crate::traits::transaction::TagConstraints;
+use crate::transaction::{LedgerBookEntry, Transaction};
+
+// only credits are disputable, disputes may drive the available
+// balance negative and any chargeback locks the account
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct DefaultPolicy;
+
+impl AccountPolicy for DefaultPolicy {
+    fn assert_is_disputable(
+        &self,
+        _account: &Account<Self>,
+        subject_tx: &LedgerBookEntry,
+        _amount: Amount,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if subject_tx.is_credit() {
+            Ok(ExecutionResult::Ok)
+        } else {
+            Err(ExecutionError::InvalidTransactionType)
+        }
+    }
+
+    fn lock_on_chargeback(
+        &self,
+        _account: &Account<Self>,
+    ) -> bool {
+        true
+    }
+}
+
+// default rules with adjustable thresholds, selectable at runtime
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ThresholdPolicy {
+    // lock once this many chargebacks are standing
+    pub lock_after_chargebacks: usize,
+
+    pub allow_negative_available: bool,
+
+    // reject withdrawals while the account is in deficit in any asset,
+    // deposits are still accepted so the client can pay it back
+    pub block_on_deficit: bool,
+}
+
+impl Default for ThresholdPolicy {
+    fn default() -> ThresholdPolicy {
+        ThresholdPolicy {
+            lock_after_chargebacks: 1,
+
+            allow_negative_available: true,
+
+            block_on_deficit: false,
+        }
+    }
+}
+
+impl AccountPolicy for ThresholdPolicy {
+    fn assert_is_active(
+        &self,
+        account: &Account<Self>,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if self.block_on_deficit && !tx.tag.is_credit() && account.in_deficit() {
+            Err(ExecutionError::AccountInDeficit)
+        } else {
+            Ok(ExecutionResult::Ok)
+        }
+    }
+
+    fn assert_is_disputable(
+        &self,
+        account: &Account<Self>,
+        subject_tx: &LedgerBookEntry,
+        amount: Amount,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if !subject_tx.is_credit() {
+            return Err(ExecutionError::InvalidTransactionType);
+        }
+
+        if !self.allow_negative_available && account.amount_available_in(subject_tx.asset) < amount {
+            return Err(ExecutionError::DisputeExceedsAvailable);
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn lock_on_chargeback(
+        &self,
+        account: &Account<Self>,
+    ) -> bool {
+        account.book_chargeback.len() >= self.lock_after_chargebacks
+    }
+}
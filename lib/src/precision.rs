@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use crate::amount::{widen, Amount};
+use crate::asset::Asset;
+use crate::execution::{ExecutionError, ExecutionResult};
+
+// largest number of decimal places a 64 bit amount can represent
+pub static MAX_DECIMALS: u32 = 18;
+
+// applied to input amounts with more decimal places than their asset
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum InputRounding {
+    Reject,
+    // to the nearest amount, ties to the even one
+    HalfEven,
+    // towards zero
+    #[default]
+    Truncate,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParsedAmount {
+    pub amount: Amount,
+
+    // digits beyond the precision of the asset were dropped or rounded
+    pub rounded: bool,
+}
+
+// number of decimal places of every asset, amounts are kept as
+// integers in the smallest unit, e.g. ten thousandths of a dollar
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssetScales {
+    pub default_decimals: u32,
+
+    decimals: BTreeMap<Asset, u32>,
+}
+
+impl Default for AssetScales {
+    fn default() -> AssetScales {
+        AssetScales {
+            default_decimals: 4,
+
+            decimals: BTreeMap::new(),
+        }
+    }
+}
+
+impl AssetScales {
+    pub fn set_decimals(
+        &mut self,
+        asset: Asset,
+        decimals: u32,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if decimals > MAX_DECIMALS {
+            return Err(ExecutionError::InvalidAmount);
+        }
+
+        self.decimals.insert(asset, decimals);
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    pub fn decimals(&self, asset: Asset) -> u32 {
+        self.decimals
+            .get(&asset)
+            .copied()
+            .unwrap_or(self.default_decimals)
+    }
+
+    // number of smallest units in one unit of the asset
+    pub fn scale(&self, asset: Asset) -> i64 {
+        10i64.pow(self.decimals(asset))
+    }
+
+    // reads a plain decimal number like -12.5, exponents are not supported
+    pub fn parse_amount(
+        &self,
+        asset: Asset,
+        text: &str,
+        rounding: InputRounding,
+    ) -> Result<ParsedAmount, ExecutionError> {
+        let text = text.trim();
+
+        let (negative, digits) =
+            match text.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, text.strip_prefix('+').unwrap_or(text)),
+            };
+
+        let (integer, fraction) =
+            match digits.find('.') {
+                None => (digits, ""),
+                Some(n) => (&digits[..n], &digits[n + 1..]),
+            };
+
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit()) {
+            return Err(ExecutionError::InvalidAmount);
+        }
+
+        let decimals = self.decimals(asset) as usize;
+
+        let (kept, dropped) =
+            if fraction.len() > decimals {
+                fraction.split_at(decimals)
+            } else {
+                (fraction, "")
+            };
+
+        let mut amount: Amount = 0;
+
+        for byte in integer.bytes().chain(kept.bytes()) {
+            amount =
+                amount.checked_mul(10)
+                    .and_then(|amount| amount.checked_add(Amount::from(byte - b'0')))
+                    .ok_or(ExecutionError::InvalidAmount)?;
+        }
+
+        amount =
+            amount.checked_mul(Amount::from(10u8).pow((decimals - kept.len()) as u32))
+                .ok_or(ExecutionError::InvalidAmount)?;
+
+        let rounded = dropped.bytes().any(|byte| byte != b'0');
+
+        if rounded {
+            let round_up =
+                match rounding {
+                    InputRounding::Reject => return Err(ExecutionError::InvalidAmount),
+                    InputRounding::Truncate => false,
+
+                    InputRounding::HalfEven => {
+                        let first = dropped.as_bytes()[0];
+                        let rest_is_zero = dropped.bytes().skip(1).all(|byte| byte == b'0');
+
+                        match first {
+                            b'5' if rest_is_zero => amount % 2 == 1,
+                            first => first >= b'5',
+                        }
+                    }
+                };
+
+            if round_up {
+                amount = amount.checked_add(1).ok_or(ExecutionError::InvalidAmount)?;
+            }
+        }
+
+        Ok(
+            ParsedAmount {
+                amount: if negative { -amount } else { amount },
+                rounded,
+            },
+        )
+    }
+
+    // shortest decimal representation, e.g. 1.5 or -2
+    pub fn format_amount(&self, asset: Asset, amount: Amount) -> String {
+        let scale = self.scale(asset) as u128;
+        let magnitude = widen(amount).unsigned_abs();
+
+        let integer = magnitude / scale;
+        let fraction = magnitude % scale;
+
+        let sign = if amount < 0 { "-" } else { "" };
+
+        if fraction == 0 {
+            return format!("{}{}", sign, integer);
+        }
+
+        let fraction =
+            format!(
+                "{:0width$}",
+                fraction,
+                width = self.decimals(asset) as usize,
+            );
+
+        format!("{}{}.{}", sign, integer, fraction.trim_end_matches('0'))
+    }
+}
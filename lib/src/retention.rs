@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::history::AccountBalance;
+use crate::id::RawTxId;
+
+// ranges of compacted ids kept apart per account, older ones are folded into
+// the highest compacted id, ids of an account are not expected to be contiguous
+pub static MAX_ID_RANGES: usize = 64;
+
+// how long an account keeps its history and the book entries booked by it,
+// compacted book entries can no longer be disputed or reversed
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Retention {
+    #[default]
+    Unlimited,
+    // number of history entries kept per account
+    Count(usize),
+    // number of ledger transactions an entry is kept for
+    Age(u64),
+    // entries are kept as long as they may be disputed, judged by their
+    // timestamp, an unlimited dispute window keeps every entry
+    DisputeWindow,
+}
+
+// what is left of the compacted entries of an account
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CompactedEntries {
+    // number of history entries compacted so far
+    pub history_entries: u64,
+    // number of book entries compacted so far
+    pub book_entries: u64,
+    // number of deposits among the compacted history entries
+    pub deposits: u64,
+    // number of closed dispute cases compacted so far
+    pub dispute_cases: u64,
+
+    // fees charged by compacted entries, per asset
+    pub fees: BTreeMap<Asset, Amount>,
+    // last compacted balance per asset, along with its sequence number
+    pub balances: BTreeMap<Asset, (u64, AccountBalance)>,
+
+    // first and last id of each range of compacted book entries, kept so
+    // that their ids may not be booked again, ids up to the floor count
+    // as compacted whether they were booked by the account or not
+    pub(crate) ids: BTreeMap<RawTxId, RawTxId>,
+    pub(crate) ids_floor: Option<RawTxId>,
+}
+
+impl CompactedEntries {
+    pub fn contains(&self, tx_id: RawTxId) -> bool {
+        if self.ids_floor.filter(|floor| tx_id <= *floor).is_some() {
+            return true;
+        }
+
+        self.ids
+            .range(..=tx_id)
+            .next_back()
+            .filter(|(_, last)| **last >= tx_id)
+            .is_some()
+    }
+
+    // never more than MAX_ID_RANGES
+    pub fn id_ranges(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub(crate) fn insert_id(&mut self, tx_id: RawTxId) {
+        if self.contains(tx_id) {
+            return;
+        }
+
+        let first =
+            match self.ids.range(..tx_id).next_back() {
+                Some((first, last)) if last.checked_add(1) == Some(tx_id) => *first,
+                _ => tx_id,
+            };
+
+        let last =
+            match tx_id.checked_add(1).and_then(|next| self.ids.remove(&next)) {
+                None => tx_id,
+                Some(last) => last,
+            };
+
+        self.ids.insert(first, last);
+
+        // the oldest ranges make way, along with every id below them
+        while self.ids.len() > MAX_ID_RANGES {
+            let first =
+                match self.ids.keys().next() {
+                    None => break,
+                    Some(first) => *first,
+                };
+
+            if let Some(last) = self.ids.remove(&first) {
+                self.ids_floor = Some(self.ids_floor.unwrap_or(last).max(last));
+            }
+        }
+    }
+}
+
+// entries held by the accounts of a ledger and compacted so far
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RetentionStats {
+    // including disputed, charged back and authorized entries and fees
+    pub book_entries: u64,
+    pub history_entries: u64,
+
+    pub compacted_book_entries: u64,
+    pub compacted_history_entries: u64,
+}
@@ -0,0 +1,147 @@
+use crate::account::Account;
+use crate::traits::policy::AccountPolicy;
+use crate::transaction::TransactionTag;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RiskConfig {
+    // disputes per deposit, in percent, above which the ratio counts against the client
+    pub max_dispute_ratio: u32,
+
+    // a withdrawal at most this many transactions after a deposit counts as rapid
+    pub rapid_withdrawal_window: usize,
+
+    // accounts scoring at least this much are flagged
+    pub flag_threshold: u32,
+}
+
+impl Default for RiskConfig {
+    fn default() -> RiskConfig {
+        RiskConfig {
+            max_dispute_ratio: 25,
+
+            rapid_withdrawal_window: 3,
+
+            flag_threshold: 50,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RiskSignal {
+    HighDisputeRatio,
+    Chargebacks(u32),
+    // withdrawals following a deposit within the rapid withdrawal window
+    RapidWithdrawals(u32),
+    DuplicateIdAttempts(u32),
+    // withdrawals rejected because the funds were held by a dispute
+    HeldFundsWithdrawals(u32),
+}
+
+impl RiskSignal {
+    fn score(&self) -> u32 {
+        match self {
+            RiskSignal::HighDisputeRatio => 30,
+            RiskSignal::Chargebacks(count) => count.saturating_mul(25),
+            RiskSignal::RapidWithdrawals(count) => count.saturating_mul(20),
+            RiskSignal::DuplicateIdAttempts(count) => count.saturating_mul(15),
+            RiskSignal::HeldFundsWithdrawals(count) => count.saturating_mul(25),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RiskScore {
+    // between 0 and 100
+    pub score: u32,
+    pub flagged: bool,
+
+    pub signals: Vec<RiskSignal>,
+}
+
+fn rapid_withdrawals<P: AccountPolicy>(
+    account: &Account<P>,
+    config: &RiskConfig,
+) -> u32 {
+    let history = account.history();
+    let mut count = 0;
+
+    for (n, entry) in history.iter().enumerate() {
+        let withdrawn =
+            match entry.tx.tag {
+                TransactionTag::Withdrawal(amount) => amount,
+                _ => continue,
+            };
+
+        // taking out at least half of a deposit made just before
+        let rapid =
+            history[..n]
+                .iter()
+                .rev()
+                .take(config.rapid_withdrawal_window)
+                .filter(|earlier| earlier.asset == entry.asset)
+                .any(|earlier| match earlier.tx.tag {
+                    TransactionTag::Deposit(amount) => withdrawn.saturating_mul(2) >= amount,
+                    _ => false,
+                });
+
+        if rapid {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+pub fn score_account<P: AccountPolicy>(
+    account: &Account<P>,
+    config: &RiskConfig,
+) -> RiskScore {
+    let mut signals = Vec::new();
+
+    // compacted deposits still count towards the dispute ratio
+    let deposits =
+        account.history()
+            .iter()
+            .filter(|entry| matches!(entry.tx.tag, TransactionTag::Deposit(_)))
+            .count()
+            + account.compacted().deposits as usize;
+
+    let disputes = account.dispute_cases().len() + account.compacted().dispute_cases as usize;
+
+    if disputes > 0 && disputes * 100 > deposits * config.max_dispute_ratio as usize {
+        signals.push(RiskSignal::HighDisputeRatio);
+    }
+
+    let chargebacks = account.book_chargeback.len() as u32;
+
+    if chargebacks > 0 {
+        signals.push(RiskSignal::Chargebacks(chargebacks));
+    }
+
+    let rapid_withdrawals = rapid_withdrawals(account, config);
+
+    if rapid_withdrawals > 0 {
+        signals.push(RiskSignal::RapidWithdrawals(rapid_withdrawals));
+    }
+
+    if account.duplicate_attempts() > 0 {
+        signals.push(RiskSignal::DuplicateIdAttempts(account.duplicate_attempts()));
+    }
+
+    if account.held_withdrawal_attempts() > 0 {
+        signals.push(RiskSignal::HeldFundsWithdrawals(account.held_withdrawal_attempts()));
+    }
+
+    let score =
+        signals.iter()
+            .map(|signal| signal.score())
+            .fold(0, u32::saturating_add)
+            .min(100);
+
+    RiskScore {
+        score,
+        flagged: score >= config.flag_threshold,
+
+        signals,
+    }
+}
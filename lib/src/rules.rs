@@ -0,0 +1,172 @@
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::fx::FxTable;
+use crate::history::HistoryEntry;
+use crate::transaction::{Transaction, TransactionTag};
+
+static SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VelocityLimit {
+    pub max_withdrawals: u32,
+    // number of transactions or seconds, depending on the rule
+    pub per: u64,
+}
+
+// withdrawal limits checked before a withdrawal is booked, rules
+// based on time are only enforced for transactions with a timestamp,
+// amounts are in the base asset and withdrawals of other assets are
+// converted at the rate in effect for them
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct WithdrawalRules {
+    // counting the withdrawal itself among the last transactions of the account
+    pub velocity_per_transactions: Option<VelocityLimit>,
+    pub velocity_per_seconds: Option<VelocityLimit>,
+
+    pub max_withdrawal: Option<Amount>,
+    // total withdrawn per day since unix epoch
+    pub daily_withdrawal_cap: Option<Amount>,
+}
+
+fn is_withdrawal(entry: &HistoryEntry) -> bool {
+    matches!(entry.tx.tag, TransactionTag::Withdrawal(_))
+}
+
+impl WithdrawalRules {
+    fn limits_amounts(&self) -> bool {
+        self.max_withdrawal.is_some() || self.daily_withdrawal_cap.is_some()
+    }
+
+    pub fn evaluate(
+        &self,
+        history: &[HistoryEntry],
+        tx: &Transaction,
+        seq: u64,
+        base_asset: Asset,
+        fx_table: Option<&FxTable>,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let amount =
+            match tx.tag {
+                TransactionTag::Withdrawal(amount) => amount,
+                _ => return Ok(ExecutionResult::Ok),
+            };
+
+        let in_base_asset = |amount: Amount, asset: Asset, seq: u64| {
+            if asset == base_asset {
+                return Ok(amount);
+            }
+
+            fx_table
+                .and_then(|fx_table| fx_table.convert(amount, asset, base_asset, seq))
+                .ok_or(ExecutionError::RateUnavailable)
+        };
+
+        let amount =
+            if self.limits_amounts() {
+                in_base_asset(amount, tx.asset.unwrap_or(base_asset), seq)?
+            } else {
+                amount
+            };
+
+        if let Some(max_withdrawal) = self.max_withdrawal {
+            if amount > max_withdrawal {
+                return Err(ExecutionError::WithdrawalLimitExceeded);
+            }
+        }
+
+        if let Some(limit) = self.velocity_per_transactions {
+            let recent = limit.per.saturating_sub(1) as usize;
+
+            let withdrawals =
+                history.iter()
+                    .rev()
+                    .take(recent)
+                    .filter(|entry| is_withdrawal(entry))
+                    .count();
+
+            if withdrawals >= limit.max_withdrawals as usize {
+                return Err(ExecutionError::VelocityLimitExceeded);
+            }
+        }
+
+        let timestamp =
+            match tx.timestamp {
+                None => return Ok(ExecutionResult::Ok),
+                Some(timestamp) => timestamp,
+            };
+
+        // earlier withdrawals less than the given number of seconds ago, most
+        // recent first, timestamps increase so older entries aren't visited
+        let withdrawn_within = |seconds: u64| {
+            history.iter()
+                .rev()
+                .take_while(move |entry| {
+                    !matches!(entry.tx.timestamp, Some(at) if timestamp.saturating_sub(at) >= seconds)
+                })
+                .filter(|entry| is_withdrawal(entry) && entry.tx.timestamp.is_some())
+        };
+
+        if let Some(limit) = self.velocity_per_seconds {
+            let withdrawals = withdrawn_within(limit.per).count();
+
+            if withdrawals >= limit.max_withdrawals as usize {
+                return Err(ExecutionError::VelocityWindowExceeded);
+            }
+        }
+
+        if let Some(daily_withdrawal_cap) = self.daily_withdrawal_cap {
+            let mut withdrawn: Amount = 0;
+
+            for entry in withdrawn_within(timestamp % SECONDS_PER_DAY + 1) {
+                withdrawn =
+                    withdrawn.saturating_add(
+                        in_base_asset(entry.amount, entry.asset, entry.seq)?,
+                    );
+            }
+
+            if withdrawn.saturating_add(amount) > daily_withdrawal_cap {
+                return Err(ExecutionError::DailyCapExceeded);
+            }
+        }
+
+        Ok(ExecutionResult::Ok)
+    }
+
+    // index of the oldest history entry the rules may still read,
+    // timestamps are expected to increase along the history
+    pub fn window_start(&self, history: &[HistoryEntry]) -> usize {
+        let mut start = history.len();
+
+        if let Some(limit) = self.velocity_per_transactions {
+            let recent = limit.per.saturating_sub(1) as usize;
+
+            start = start.min(history.len().saturating_sub(recent));
+        }
+
+        let latest =
+            match history.last().and_then(|entry| entry.tx.timestamp) {
+                None => return start,
+                Some(latest) => latest,
+            };
+
+        // entries before the first one within the given number of seconds
+        let expired = |seconds: u64| {
+            history.partition_point(|entry| {
+                entry.tx.timestamp
+                    .filter(|timestamp| latest.saturating_sub(*timestamp) >= seconds)
+                    .is_some()
+            })
+        };
+
+        if let Some(limit) = self.velocity_per_seconds {
+            start = start.min(expired(limit.per));
+        }
+
+        if self.daily_withdrawal_cap.is_some() {
+            start = start.min(expired(latest % SECONDS_PER_DAY + 1));
+        }
+
+        start
+    }
+}
@@ -0,0 +1,57 @@
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::dispute::DisputeCase;
+use crate::id::{RawClientId, RawTxId};
+use crate::transaction::LedgerBookEntry;
+
+// handle returned by a savepoint, only valid for the ledger that issued it
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Savepoint {
+    // unique per ledger, handles of released savepoints never match again
+    pub(crate) id: u64,
+    pub(crate) depth: usize,
+    pub(crate) journal_len: usize,
+    pub(crate) sequence: u64,
+    pub(crate) postings_len: usize,
+}
+
+// state of a single account prior to the execution of a transaction,
+// every transaction only ever touches the book entries of its own id
+// so capturing those is enough to revert it without cloning any books
+#[derive(Debug, Clone)]
+pub(crate) struct AccountUndo {
+    pub tx_id: RawTxId,
+
+    pub is_locked: bool,
+    pub credit_limit: Amount,
+
+    // only the balance in the asset of the transaction is touched
+    pub asset: Asset,
+    pub amount_available: Option<Amount>,
+
+    pub book: Option<LedgerBookEntry>,
+    pub book_disputed: Option<LedgerBookEntry>,
+    pub book_chargeback: Option<LedgerBookEntry>,
+    pub book_authorized: Option<LedgerBookEntry>,
+
+    pub history_len: usize,
+    pub book_fees_len: usize,
+
+    pub dispute_cases_len: usize,
+    // prior state of the case referenced by the transaction
+    pub dispute_case: Option<DisputeCase>,
+
+    // compacted entries are left out, accounts are
+    // not compacted while a savepoint is open
+    pub current_seq: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct JournalEntry {
+    pub client_id: RawClientId,
+
+    // account did not exist before the transaction
+    pub created: bool,
+
+    pub undo: AccountUndo,
+}
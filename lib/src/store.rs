@@ -0,0 +1,257 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::account::Account;
+use crate::config::AccountConfig;
+use crate::execution::StoreError;
+use crate::id::RawClientId;
+use crate::traits::policy::AccountPolicy;
+use crate::traits::store::AccountStore;
+
+// keeps the most recently used accounts in memory and writes the others
+// out to a file per account, in a directory owned by the store
+pub struct DiskAccountStore<P: AccountPolicy> {
+    dir: PathBuf,
+
+    // the ones of the ledger, accounts are read back with them
+    account_config: AccountConfig,
+    policy: P,
+
+    // maximum number of accounts held in memory at once
+    capacity: usize,
+
+    // in memory, along with the tick they were last used at
+    cached: BTreeMap<RawClientId, (u64, Account<P>)>,
+    // least recently used first
+    recency: BTreeMap<u64, RawClientId>,
+    tick: u64,
+
+    // accounts with a file, which is stale while they are in memory
+    on_disk: BTreeSet<RawClientId>,
+    // number of accounts written out so far
+    writes: u64,
+}
+
+impl<P: AccountPolicy> DiskAccountStore<P> {
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        capacity: usize,
+        account_config: AccountConfig,
+        policy: P,
+    ) -> io::Result<DiskAccountStore<P>> {
+        let dir = dir.into();
+
+        fs::create_dir_all(&dir)?;
+
+        Ok(
+            DiskAccountStore {
+                dir,
+
+                account_config,
+                policy,
+
+                capacity: capacity.max(1),
+
+                cached: BTreeMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+
+                on_disk: BTreeSet::new(),
+                writes: 0,
+            },
+        )
+    }
+
+    pub fn in_memory(&self) -> usize {
+        self.cached.len()
+    }
+
+    // accounts currently only held on disk
+    pub fn spilled(&self) -> usize {
+        self.on_disk
+            .iter()
+            .filter(|client_id| !self.cached.contains_key(client_id))
+            .count()
+    }
+
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+
+    fn path(&self, client_id: RawClientId) -> PathBuf {
+        self.dir.join(format!("{}.account", client_id))
+    }
+
+    fn read(
+        &self,
+        client_id: RawClientId,
+    ) -> Result<Account<P>, StoreError> {
+        let bytes =
+            fs::read(self.path(client_id))
+                .map_err(|_| StoreError::ReadFailed)?;
+
+        Account::decode_state(&bytes, self.account_config, self.policy.clone())
+            .ok_or(StoreError::ReadFailed)
+    }
+
+    fn touch(&mut self, client_id: RawClientId) {
+        self.tick += 1;
+
+        if let Some((tick, _)) = self.cached.get_mut(&client_id) {
+            self.recency.remove(tick);
+            self.recency.insert(self.tick, client_id);
+
+            *tick = self.tick;
+        }
+    }
+
+    // writes out the least recently used accounts until there is room for
+    // one more, an account stays in memory if it could not be written
+    fn make_room(&mut self) -> Result<(), StoreError> {
+        while self.cached.len() >= self.capacity {
+            let (tick, client_id) =
+                match self.recency.iter().next() {
+                    None => break,
+                    Some((tick, client_id)) => (*tick, *client_id),
+                };
+
+            if let Some((_, account)) = self.cached.get(&client_id) {
+                let mut bytes = Vec::new();
+                account.encode_state(&mut bytes);
+
+                fs::write(self.path(client_id), bytes)
+                    .map_err(|_| StoreError::WriteFailed)?;
+            }
+
+            self.recency.remove(&tick);
+            self.cached.remove(&client_id);
+            self.on_disk.insert(client_id);
+            self.writes += 1;
+        }
+
+        Ok(())
+    }
+
+    fn cache(&mut self, account: Account<P>) -> Result<&mut Account<P>, StoreError> {
+        let client_id = account.id();
+
+        self.make_room()?;
+
+        self.tick += 1;
+        self.recency.insert(self.tick, client_id);
+
+        let (_, account) =
+            self.cached
+                .entry(client_id)
+                .or_insert((self.tick, account));
+
+        Ok(account)
+    }
+}
+
+impl<P: AccountPolicy> AccountStore<P> for DiskAccountStore<P> {
+    fn get(
+        &self,
+        client_id: RawClientId,
+    ) -> Result<Option<Cow<'_, Account<P>>>, StoreError> {
+        if let Some((_, account)) = self.cached.get(&client_id) {
+            return Ok(Some(Cow::Borrowed(account)));
+        }
+
+        if !self.on_disk.contains(&client_id) {
+            return Ok(None);
+        }
+
+        self.read(client_id).map(|account| Some(Cow::Owned(account)))
+    }
+
+    fn get_mut(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<&mut Account<P>>, StoreError> {
+        if self.cached.contains_key(&client_id) {
+            self.touch(client_id);
+
+            return Ok(self.cached.get_mut(&client_id).map(|(_, account)| account));
+        }
+
+        if !self.on_disk.contains(&client_id) {
+            return Ok(None);
+        }
+
+        let account = self.read(client_id)?;
+
+        self.cache(account).map(Some)
+    }
+
+    fn insert(
+        &mut self,
+        account: Account<P>,
+    ) -> Result<&mut Account<P>, StoreError> {
+        let client_id = account.id();
+
+        if let Some((tick, _)) = self.cached.remove(&client_id) {
+            self.recency.remove(&tick);
+        }
+
+        self.cache(account)
+    }
+
+    fn remove(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<Account<P>>, StoreError> {
+        let account =
+            match self.cached.remove(&client_id) {
+                Some((tick, account)) => {
+                    self.recency.remove(&tick);
+
+                    Some(account)
+                }
+
+                None if self.on_disk.contains(&client_id) => Some(self.read(client_id)?),
+                None => None,
+            };
+
+        // a file left behind is never read again
+        if self.on_disk.remove(&client_id) {
+            let _ = fs::remove_file(self.path(client_id));
+        }
+
+        Ok(account)
+    }
+
+    fn contains(
+        &self,
+        client_id: RawClientId,
+    ) -> bool {
+        self.cached.contains_key(&client_id) || self.on_disk.contains(&client_id)
+    }
+
+    fn client_ids(
+        &self,
+    ) -> Vec<RawClientId> {
+        self.cached
+            .keys()
+            .chain(self.on_disk.iter())
+            .copied()
+            .collect::<BTreeSet<RawClientId>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+// the files are only meaningful to the store that wrote them
+impl<P: AccountPolicy> Drop for DiskAccountStore<P> {
+    fn drop(&mut self) {
+        for client_id in self.on_disk.iter() {
+            let _ = fs::remove_file(self.path(*client_id));
+        }
+
+        // left in place if anything else was put there
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
@@ -11,6 +11,11 @@ pub trait AccountDebitCredit {
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn set_credit_limit(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
 }
 
 pub trait AccountBookEntry {
@@ -28,6 +33,11 @@ pub trait AccountBookEntry {
         &self,
         tx: &Transaction,
     ) -> Result<&LedgerBookEntry, ExecutionError>;
+
+    fn find_authorized_book_entry(
+        &self,
+        tx: &Transaction,
+    ) -> Result<&LedgerBookEntry, ExecutionError>;
 }
 
 pub trait AccountBookActions {
@@ -45,4 +55,67 @@ pub trait AccountBookActions {
         &mut self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn represent_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn second_chargeback_book_entry(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
+
+pub trait AccountTransfers {
+    fn transfer_out(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn transfer_in(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn reverse_transfer(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
+
+pub trait AccountFees {
+    fn charge_fee(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
+
+pub trait AccountConversions {
+    fn convert_out(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn convert_in(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
+
+pub trait AccountAuthorizations {
+    fn authorize(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn capture_authorization(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn void_authorization(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
 }
@@ -7,3 +7,11 @@ pub trait TransactionExecution {
         tx: &Transaction,
     ) -> Result<ExecutionResult, ExecutionError>;
 }
+
+pub trait SequencedTransactionExecution {
+    fn execute_sequenced_transaction(
+        &mut self,
+        seq: u64,
+        tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
@@ -1,3 +1,6 @@
 pub mod account;
 pub mod execution;
+pub mod policy;
+pub mod savepoint;
+pub mod store;
 pub mod transaction;
@@ -0,0 +1,29 @@
+use crate::account::Account;
+use crate::amount::Amount;
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::transaction::{LedgerBookEntry, Transaction};
+
+pub trait AccountPolicy: Clone + Sized {
+    // checked before any balance flow initiated by the client
+    fn assert_is_active(
+        &self,
+        _account: &Account<Self>,
+        _tx: &Transaction,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        Ok(ExecutionResult::Ok)
+    }
+
+    // checked before a book entry is disputed for the given amount
+    fn assert_is_disputable(
+        &self,
+        account: &Account<Self>,
+        subject_tx: &LedgerBookEntry,
+        amount: Amount,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    // consulted right after a chargeback has been booked
+    fn lock_on_chargeback(
+        &self,
+        account: &Account<Self>,
+    ) -> bool;
+}
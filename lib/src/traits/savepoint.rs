@@ -0,0 +1,24 @@
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::savepoint::Savepoint;
+use crate::transaction::Transaction;
+
+pub trait LedgerSavepoints {
+    fn savepoint(
+        &mut self,
+    ) -> Savepoint;
+
+    fn rollback_to(
+        &mut self,
+        savepoint: Savepoint,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn release_savepoint(
+        &mut self,
+        savepoint: Savepoint,
+    ) -> Result<ExecutionResult, ExecutionError>;
+
+    fn execute_batch(
+        &mut self,
+        txs: &[Transaction],
+    ) -> Result<ExecutionResult, ExecutionError>;
+}
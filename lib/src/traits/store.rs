@@ -0,0 +1,162 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+
+use crate::account::Account;
+use crate::execution::StoreError;
+use crate::id::RawClientId;
+use crate::traits::policy::AccountPolicy;
+
+// where the ledger keeps its accounts, stores may hold only part of
+// them in memory as long as every account reads back as it was left
+pub trait AccountStore<P: AccountPolicy> {
+    // borrowed if the account is in memory, read back otherwise
+    fn get(
+        &self,
+        client_id: RawClientId,
+    ) -> Result<Option<Cow<'_, Account<P>>>, StoreError>;
+
+    fn get_mut(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<&mut Account<P>>, StoreError>;
+
+    // replaces an account with the same id
+    fn insert(
+        &mut self,
+        account: Account<P>,
+    ) -> Result<&mut Account<P>, StoreError>;
+
+    fn remove(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<Account<P>>, StoreError>;
+
+    fn contains(
+        &self,
+        client_id: RawClientId,
+    ) -> bool;
+
+    // ascending
+    fn client_ids(
+        &self,
+    ) -> Vec<RawClientId>;
+
+    // ordered by client id, accounts that can't be read back yield an error
+    fn iter(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<Cow<'_, Account<P>>, StoreError>> + '_> {
+        Box::new(
+            self.client_ids()
+                .into_iter()
+                .filter_map(move |client_id| self.get(client_id).transpose()),
+        )
+    }
+}
+
+// keeps every account in memory
+impl<P: AccountPolicy> AccountStore<P> for BTreeMap<RawClientId, Account<P>> {
+    fn get(
+        &self,
+        client_id: RawClientId,
+    ) -> Result<Option<Cow<'_, Account<P>>>, StoreError> {
+        Ok(BTreeMap::get(self, &client_id).map(Cow::Borrowed))
+    }
+
+    fn get_mut(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<&mut Account<P>>, StoreError> {
+        Ok(BTreeMap::get_mut(self, &client_id))
+    }
+
+    fn insert(
+        &mut self,
+        account: Account<P>,
+    ) -> Result<&mut Account<P>, StoreError> {
+        match self.entry(account.id()) {
+            Entry::Vacant(entry) => Ok(entry.insert(account)),
+            Entry::Occupied(mut entry) => {
+                entry.insert(account);
+
+                Ok(entry.into_mut())
+            }
+        }
+    }
+
+    fn remove(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<Account<P>>, StoreError> {
+        Ok(BTreeMap::remove(self, &client_id))
+    }
+
+    fn contains(
+        &self,
+        client_id: RawClientId,
+    ) -> bool {
+        self.contains_key(&client_id)
+    }
+
+    fn client_ids(
+        &self,
+    ) -> Vec<RawClientId> {
+        self.keys().copied().collect()
+    }
+
+    fn iter(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<Cow<'_, Account<P>>, StoreError>> + '_> {
+        Box::new(self.values().map(|account| Ok(Cow::Borrowed(account))))
+    }
+}
+
+// lets the store be chosen at runtime
+impl<P: AccountPolicy> AccountStore<P> for Box<dyn AccountStore<P>> {
+    fn get(
+        &self,
+        client_id: RawClientId,
+    ) -> Result<Option<Cow<'_, Account<P>>>, StoreError> {
+        (**self).get(client_id)
+    }
+
+    fn get_mut(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<&mut Account<P>>, StoreError> {
+        (**self).get_mut(client_id)
+    }
+
+    fn insert(
+        &mut self,
+        account: Account<P>,
+    ) -> Result<&mut Account<P>, StoreError> {
+        (**self).insert(account)
+    }
+
+    fn remove(
+        &mut self,
+        client_id: RawClientId,
+    ) -> Result<Option<Account<P>>, StoreError> {
+        (**self).remove(client_id)
+    }
+
+    fn contains(
+        &self,
+        client_id: RawClientId,
+    ) -> bool {
+        (**self).contains(client_id)
+    }
+
+    fn client_ids(
+        &self,
+    ) -> Vec<RawClientId> {
+        (**self).client_ids()
+    }
+
+    fn iter(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<Cow<'_, Account<P>>, StoreError>> + '_> {
+        (**self).iter()
+    }
+}
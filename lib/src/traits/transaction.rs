@@ -1,11 +1,18 @@
+use crate::amount::Amount;
 use crate::execution::ExecutionError;
 
 pub trait TagConstraints {
     fn is_deposit(&self) -> bool;
     fn is_withdrawal(&self) -> bool;
+    // deposits and incoming transfers
+    fn is_credit(&self) -> bool;
 }
 
 pub trait BookEntryExt {
-    fn deposit_amount(&self) -> Result<i64, ExecutionError>;
-    fn withdrawal_amount(&self) -> Result<i64, ExecutionError>;
+    fn deposit_amount(&self) -> Result<Amount, ExecutionError>;
+    fn withdrawal_amount(&self) -> Result<Amount, ExecutionError>;
+    fn authorized_amount(&self) -> Result<Amount, ExecutionError>;
+    // amounts left after reversals
+    fn credited_amount(&self) -> Result<Amount, ExecutionError>;
+    fn transferred_amount(&self) -> Result<Amount, ExecutionError>;
 }
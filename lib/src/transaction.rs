@@ -1,19 +1,116 @@
-use crate::execution::ExecutionError;
+use crate::amount::Amount;
+use crate::asset::Asset;
+use crate::execution::{ExecutionError, ExecutionResult};
+use crate::id::{ClientId, RawClientId, RawTxId, TxId};
 use crate::traits::transaction::{BookEntryExt, TagConstraints};
 
-// amounts are in tenth of cent precision
+// amounts are in the smallest unit of their asset, see precision
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum TransactionTag {
     // balance flows
 
-    Deposit(i64),
-    Withdrawal(i64),
+    Deposit(Amount),
+    Withdrawal(Amount),
 
-    // administrative
+    // administrative, optionally limited to part of the referenced deposit
 
-    Dispute,
-    Resolve,
-    Chargeback,
+    Dispute(Option<Amount>),
+    Resolve(Option<Amount>),
+    Chargeback(Option<Amount>),
+
+    // merchant won representment, reverses the chargeback
+    Representment,
+    // pre-arbitration, charges a represented deposit back again
+    SecondChargeback,
+
+    // card flow, reserves funds until the authorization is settled
+
+    Authorize(Amount),
+    // settles the authorization as a withdrawal
+    Capture,
+    // releases the authorization
+    Void,
+
+    // funds moved between clients, the ledger applies both legs at once
+
+    // recipient and amount, submitted by the sender
+    Transfer(RawClientId, Amount),
+    // sender and amount, credited to the recipient by the ledger
+    TransferIn(RawClientId, Amount),
+    // returns (part of) a transfer to the sender, submitted by the sender
+    TransferReversal(Option<Amount>),
+
+    // charged by the ledger according to its fee schedule
+    Fee(Amount),
+
+    // booked by the ledger to fund a transaction in another asset,
+    // amounts are in the asset of the transaction
+
+    // asset bought and amount sold
+    ConversionOut(Asset, Amount),
+    // asset sold and amount bought
+    ConversionIn(Asset, Amount),
+
+    // sets the overdraft the client may draw on
+    CreditLimit(Amount),
+}
+
+impl TransactionTag {
+    // amounts moved must be positive, as must partial amounts,
+    // credit limits and fees must not be negative
+    pub fn assert_is_valid(&self) -> Result<ExecutionResult, ExecutionError> {
+        let is_valid =
+            match *self {
+                TransactionTag::Deposit(amount)
+                | TransactionTag::Withdrawal(amount)
+                | TransactionTag::Authorize(amount)
+                | TransactionTag::Transfer(_, amount)
+                | TransactionTag::TransferIn(_, amount)
+                | TransactionTag::ConversionOut(_, amount)
+                | TransactionTag::ConversionIn(_, amount) => amount > 0,
+
+                TransactionTag::Dispute(amount)
+                | TransactionTag::Resolve(amount)
+                | TransactionTag::Chargeback(amount)
+                | TransactionTag::TransferReversal(amount) => amount.filter(|amount| *amount <= 0).is_none(),
+
+                TransactionTag::Fee(amount)
+                | TransactionTag::CreditLimit(amount) => amount >= 0,
+
+                TransactionTag::Representment
+                | TransactionTag::SecondChargeback
+                | TransactionTag::Capture
+                | TransactionTag::Void => true,
+            };
+
+        if is_valid {
+            Ok(ExecutionResult::Ok)
+        } else {
+            Err(ExecutionError::InvalidAmount)
+        }
+    }
+
+    pub fn partial_amount(&self) -> Option<Amount> {
+        match self {
+            TransactionTag::Dispute(amount)
+            | TransactionTag::Resolve(amount)
+            | TransactionTag::Chargeback(amount)
+            | TransactionTag::TransferReversal(amount) => *amount,
+
+            _ => None,
+        }
+    }
+
+    // legs only ever booked by the ledger on behalf of another transaction
+    pub fn is_ledger_leg(&self) -> bool {
+        matches!(
+            self,
+            TransactionTag::TransferIn(_, _)
+            | TransactionTag::Fee(_)
+            | TransactionTag::ConversionOut(_, _)
+            | TransactionTag::ConversionIn(_, _)
+        )
+    }
 }
 
 impl TagConstraints for TransactionTag {
@@ -32,52 +129,306 @@ impl TagConstraints for TransactionTag {
             false
         }
     }
+
+    fn is_credit(&self) -> bool {
+        matches!(
+            self,
+            TransactionTag::Deposit(_)
+            | TransactionTag::TransferIn(_, _)
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Transaction {
-    pub id: u32,
-    pub client_id: u16,
+    pub id: RawTxId,
+    pub client_id: RawClientId,
     pub tag: TransactionTag,
+
+    // seconds since unix epoch, if known
+    pub timestamp: Option<u64>,
+
+    // base asset of the account if not given, administrative
+    // transactions act in the asset of the referenced entry
+    pub asset: Option<Asset>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct LedgerBookEntry(pub TransactionTag);
+pub struct LedgerBookEntry {
+    pub tag: TransactionTag,
+    pub timestamp: Option<u64>,
+    pub asset: Asset,
+
+    // portion of the deposit currently held by a dispute
+    pub disputed: Amount,
+    // portion of the deposit that has been charged back
+    pub charged_back: Amount,
+
+    // number of disputes opened against the deposit so far
+    pub disputes: u32,
+    // latest dispute case, cleared once resolved
+    pub case_id: Option<u32>,
+
+    // portion of a transfer that has been reversed
+    pub reversed: Amount,
+}
+
+// validated counterparts to building the struct, which stays available
+// for tests, timestamp and asset may be set with struct update syntax
+impl Transaction {
+    pub fn new(
+        client: ClientId,
+        tx: TxId,
+        tag: TransactionTag,
+    ) -> Result<Transaction, ExecutionError> {
+        tag.assert_is_valid()?;
+
+        Ok(
+            Transaction {
+                id: tx.0,
+                client_id: client.0,
+                tag,
+                timestamp: None,
+                asset: None,
+            },
+        )
+    }
+
+    pub fn deposit(
+        client: ClientId,
+        tx: TxId,
+        amount: Amount,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Deposit(amount),
+        )
+    }
+
+    pub fn withdrawal(
+        client: ClientId,
+        tx: TxId,
+        amount: Amount,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Withdrawal(amount),
+        )
+    }
+
+    // administrative, None acts on everything left of the referenced transaction
+
+    pub fn dispute(
+        client: ClientId,
+        tx: TxId,
+        amount: Option<Amount>,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Dispute(amount),
+        )
+    }
+
+    pub fn resolve(
+        client: ClientId,
+        tx: TxId,
+        amount: Option<Amount>,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Resolve(amount),
+        )
+    }
+
+    pub fn chargeback(
+        client: ClientId,
+        tx: TxId,
+        amount: Option<Amount>,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Chargeback(amount),
+        )
+    }
+
+    pub fn representment(
+        client: ClientId,
+        tx: TxId,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Representment,
+        )
+    }
+
+    pub fn second_chargeback(
+        client: ClientId,
+        tx: TxId,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::SecondChargeback,
+        )
+    }
+
+    // card flow
+
+    pub fn authorize(
+        client: ClientId,
+        tx: TxId,
+        amount: Amount,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Authorize(amount),
+        )
+    }
+
+    pub fn capture(
+        client: ClientId,
+        tx: TxId,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Capture,
+        )
+    }
+
+    pub fn void(
+        client: ClientId,
+        tx: TxId,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Void,
+        )
+    }
+
+    // transfers
+
+    pub fn transfer(
+        client: ClientId,
+        tx: TxId,
+        recipient: ClientId,
+        amount: Amount,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::Transfer(recipient.0, amount),
+        )
+    }
+
+    pub fn transfer_reversal(
+        client: ClientId,
+        tx: TxId,
+        amount: Option<Amount>,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::TransferReversal(amount),
+        )
+    }
+
+    pub fn credit_limit(
+        client: ClientId,
+        tx: TxId,
+        limit: Amount,
+    ) -> Result<Transaction, ExecutionError> {
+        Transaction::new(
+            client,
+            tx,
+            TransactionTag::CreditLimit(limit),
+        )
+    }
+
+    pub fn client(&self) -> ClientId {
+        ClientId(self.client_id)
+    }
+
+    pub fn tx(&self) -> TxId {
+        TxId(self.id)
+    }
+}
 
 impl BookEntryExt for LedgerBookEntry {
-    fn deposit_amount(&self) -> Result<i64, ExecutionError> {
-        match self.0 {
+    fn deposit_amount(&self) -> Result<Amount, ExecutionError> {
+        match self.tag {
             TransactionTag::Deposit(amount) => Ok(amount),
             _ => Err(ExecutionError::InvalidTransactionType),
         }
     }
 
-    fn withdrawal_amount(&self) -> Result<i64, ExecutionError> {
-        match self.0 {
+    fn withdrawal_amount(&self) -> Result<Amount, ExecutionError> {
+        match self.tag {
             TransactionTag::Withdrawal(amount) => Ok(amount),
             _ => Err(ExecutionError::InvalidTransactionType),
         }
     }
+
+    fn authorized_amount(&self) -> Result<Amount, ExecutionError> {
+        match self.tag {
+            TransactionTag::Authorize(amount) => Ok(amount),
+            _ => Err(ExecutionError::InvalidTransactionType),
+        }
+    }
+
+    fn credited_amount(&self) -> Result<Amount, ExecutionError> {
+        match self.tag {
+            TransactionTag::Deposit(amount)
+            | TransactionTag::TransferIn(_, amount) => Ok(amount - self.reversed),
+            _ => Err(ExecutionError::InvalidTransactionType),
+        }
+    }
+
+    fn transferred_amount(&self) -> Result<Amount, ExecutionError> {
+        match self.tag {
+            TransactionTag::Transfer(_, amount)
+            | TransactionTag::TransferIn(_, amount) => Ok(amount - self.reversed),
+            _ => Err(ExecutionError::InvalidTransactionType),
+        }
+    }
 }
 
 impl TagConstraints for LedgerBookEntry {
     fn is_deposit(&self) -> bool {
-        let LedgerBookEntry(ref tag) = self;
-
-        tag.is_deposit()
+        self.tag.is_deposit()
     }
 
     fn is_withdrawal(&self) -> bool {
-        let LedgerBookEntry(ref tag) = self;
+        self.tag.is_withdrawal()
+    }
 
-        tag.is_withdrawal()
+    fn is_credit(&self) -> bool {
+        self.tag.is_credit()
     }
 }
 
 impl Into<LedgerBookEntry> for Transaction {
     fn into(self) -> LedgerBookEntry {
-        LedgerBookEntry(
-            self.tag.clone(),
-        )
+        LedgerBookEntry {
+            tag: self.tag,
+            timestamp: self.timestamp,
+            asset: self.asset.unwrap_or_default(),
+
+            disputed: 0,
+            charged_back: 0,
+
+            disputes: 0,
+            case_id: None,
+
+            reversed: 0,
+        }
     }
 }
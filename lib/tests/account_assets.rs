@@ -0,0 +1,206 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn eur() -> Asset {
+    Asset::new("EUR").unwrap()
+}
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+    asset: Option<Asset>,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset,
+    }
+}
+
+// (available, held) in the base asset and in euro
+type TestCasesAssets = Vec<(Vec<(Transaction, Result<ExecutionResult, ExecutionError>)>, ((Amount, Amount), (Amount, Amount)))>;
+
+fn run_test_assets(cases: TestCasesAssets) {
+    for (txs, exp_balances) in cases.iter() {
+        let mut account =
+            Account::with_config(
+                CLIENT_ID_A,
+                AccountConfig {
+                    credit_limit: 10000,
+                    ..AccountConfig::default()
+                },
+            );
+
+        for (tx, exp_result) in txs.iter() {
+            assert_eq!(
+                &account.execute_transaction(tx),
+                exp_result,
+            );
+        }
+
+        assert_eq!(
+            (
+                (account.amount_available(), account.amount_held()),
+                (account.amount_available_in(eur()), account.amount_held_in(eur())),
+            ),
+            *exp_balances,
+        );
+    }
+}
+
+#[test]
+fn asset_codes() {
+    assert_eq!(Asset::new("eur"), Some(eur()));
+    assert_eq!(Asset::new("BTC").map(|asset| asset.to_string()), Some(String::from("BTC")));
+    assert_eq!(Asset::default().code(), "USD");
+
+    assert_eq!(Asset::new(""), None);
+    assert_eq!(Asset::new("EU R"), None);
+    assert_eq!(Asset::new("LONGCODE1"), None);
+}
+
+#[test]
+fn account_balances_per_asset() {
+    run_test_assets(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000), None),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000), Some(eur())),
+                        Ok(ExecutionResult::NewAvailableBalance(5000)),
+                    ),
+                    (
+                        // the overdraft only covers the base asset
+                        fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(6000), Some(eur())),
+                        Err(ExecutionError::InsufficientBalance),
+                    ),
+                    (
+                        fake_tx(4, CLIENT_ID_A, TransactionTag::Withdrawal(2000), Some(eur())),
+                        Ok(ExecutionResult::NewAvailableBalance(3000)),
+                    ),
+                    (
+                        fake_tx(5, CLIENT_ID_A, TransactionTag::Withdrawal(15000), None),
+                        Ok(ExecutionResult::NewAvailableBalance(-5000)),
+                    ),
+                    (
+                        // ids are unique across assets
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000), None),
+                        Err(ExecutionError::TransactionExists),
+                    ),
+                ),
+                ((-5000, 0), (3000, 0)),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000), None),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000), Some(eur())),
+                        Ok(ExecutionResult::NewAvailableBalance(5000)),
+                    ),
+                    (
+                        // acts in the asset of the disputed deposit
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Dispute(None), None),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None), Some(eur())),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Resolve(Some(2000)), Some(eur())),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                ),
+                ((10000, 0), (2000, 3000)),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_general_ledger_balanced_per_asset() {
+    let mut ledger = Ledger::new();
+    ledger.enable_general_ledger();
+
+    let txs = vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000), None),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000), Some(eur())),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 4000), Some(eur())),
+        fake_tx(4, CLIENT_ID_B, TransactionTag::Withdrawal(1000), Some(eur())),
+    );
+
+    for tx in txs.iter() {
+        assert!(ledger.execute_transaction(tx).is_ok());
+    }
+
+    let account_b = &ledger.accounts()[&CLIENT_ID_B];
+
+    assert_eq!(account_b.assets(), vec!(eur(), Asset::default()));
+    assert_eq!(account_b.amount_available_in(eur()), 3000);
+    assert_eq!(account_b.amount_available(), 0);
+
+    let general_ledger = ledger.general_ledger().unwrap();
+
+    assert!(general_ledger.is_balanced());
+    assert!(
+        general_ledger
+            .trial_balance()
+            .iter()
+            .any(|row| row.asset == eur() && row.debit == 4000)
+    );
+}
+
+#[test]
+fn account_partial_amounts_in_the_asset_of_the_entry() {
+    run_test_assets(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000), Some(eur())),
+                        Ok(ExecutionResult::NewAvailableBalance(15000)),
+                    ),
+                    (
+                        // an amount without an asset is in the base asset
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(Some(5000)), None),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(Some(5000)), Some(eur())),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Resolve(Some(2000)), None),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                    (
+                        // disputes without an amount need no asset
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None), None),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                ),
+                ((0, 0), (10000, 0)),
+            ),
+        ),
+    );
+}
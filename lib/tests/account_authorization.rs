@@ -0,0 +1,182 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+// (available, held by disputes, held by authorizations, total)
+type TestCasesAuthorization = Vec<(Vec<Transaction>, (Amount, Amount, Amount, Amount))>;
+
+fn run_test_authorization(cases: TestCasesAuthorization) {
+    for (txs, exp_balances) in cases.iter() {
+        let mut account = Account::new(CLIENT_ID_A);
+
+        for tx in txs.iter() {
+            let _ = account.execute_transaction(tx);
+        }
+
+        assert_eq!(
+            (
+                account.amount_available(),
+                account.amount_held_disputed(),
+                account.amount_held_authorized(),
+                account.amount_total(),
+            ),
+            *exp_balances,
+        );
+
+        assert_eq!(
+            account.amount_held(),
+            account.amount_held_disputed() + account.amount_held_authorized(),
+        );
+    }
+}
+
+#[test]
+fn account_authorize_capture_void_balances() {
+    run_test_authorization(
+        vec!(
+            (
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Authorize(5000)),
+                ),
+                (10000, 0, 5000, 15000),
+            ),
+            (
+                // captured funds leave the account
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Authorize(5000)),
+                    fake_tx(2, TransactionTag::Capture),
+                ),
+                (10000, 0, 0, 10000),
+            ),
+            (
+                // voided funds return to available
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Authorize(5000)),
+                    fake_tx(2, TransactionTag::Void),
+                ),
+                (15000, 0, 0, 15000),
+            ),
+            (
+                // authorizations can't exceed the available balance
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Authorize(20000)),
+                ),
+                (15000, 0, 0, 15000),
+            ),
+            (
+                // dispute and authorization holds are kept apart
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Deposit(10000)),
+                    fake_tx(3, TransactionTag::Authorize(5000)),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                ),
+                (5000, 15000, 5000, 25000),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_verify_authorization_contract() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, TransactionTag::Deposit(15000)),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+        ),
+        (
+            fake_tx(2, TransactionTag::Capture),
+            Err(ExecutionError::InvalidTransaction),
+        ),
+        (
+            fake_tx(2, TransactionTag::Authorize(5000)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+        (
+            fake_tx(2, TransactionTag::Withdrawal(1)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(2, TransactionTag::Authorize(1)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            // pending authorizations can't be disputed
+            fake_tx(2, TransactionTag::Dispute(None)),
+            Err(ExecutionError::InvalidTransaction),
+        ),
+        (
+            fake_tx(2, TransactionTag::Capture),
+            Ok(ExecutionResult::Ok),
+        ),
+        (
+            fake_tx(2, TransactionTag::Void),
+            Err(ExecutionError::InvalidTransaction),
+        ),
+        (
+            // captured authorizations are withdrawals
+            fake_tx(2, TransactionTag::Dispute(None)),
+            Err(ExecutionError::InvalidTransactionType),
+        ),
+    ).iter() {
+        assert_eq!(
+            &account.execute_transaction(tx),
+            exp_result,
+        );
+    }
+}
+
+#[test]
+fn account_settle_authorization_while_locked() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for tx in vec!(
+        fake_tx(1, TransactionTag::Deposit(15000)),
+        fake_tx(2, TransactionTag::Deposit(10000)),
+        fake_tx(3, TransactionTag::Authorize(5000)),
+        fake_tx(4, TransactionTag::Authorize(5000)),
+        fake_tx(1, TransactionTag::Dispute(None)),
+        fake_tx(1, TransactionTag::Chargeback(None)),
+    ).iter() {
+        account.execute_transaction(tx).unwrap();
+    }
+
+    assert_eq!(
+        account.execute_transaction(&fake_tx(5, TransactionTag::Authorize(1))),
+        Err(ExecutionError::AccountLocked),
+    );
+
+    assert_eq!(
+        account.execute_transaction(&fake_tx(3, TransactionTag::Capture)),
+        Ok(ExecutionResult::Ok),
+    );
+
+    assert_eq!(
+        account.execute_transaction(&fake_tx(4, TransactionTag::Void)),
+        Ok(ExecutionResult::NewAvailableBalance(5000)),
+    );
+}
@@ -1,22 +1,26 @@
 use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::id::{RawClientId, RawTxId};
 use simledger::traits::execution::TransactionExecution;
 use simledger::transaction::{Transaction, TransactionTag};
 
-static CLIENT_ID_A: u16 = 12;
-static CLIENT_ID_B: u16 = 21;
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
 
 fn fake_tx(
-    id: u32,
+    id: RawTxId,
     tag: TransactionTag,
 ) -> Transaction {
     Transaction {
         id,
         client_id: CLIENT_ID_A,
         tag,
+        timestamp: None,
+        asset: None,
     }
 }
 
-type TestCasesBalances = Vec<(Vec<Transaction>, (i64, i64, i64, bool))>;
+type TestCasesBalances = Vec<(Vec<Transaction>, (Amount, Amount, Amount, bool))>;
 
 fn run_test_balances(cases: TestCasesBalances) {
     for case in cases.iter() {
@@ -102,7 +106,7 @@ fn account_verify_tx_reverse_hijack_by_reusing_id_impossible() {
                 vec!(
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     fake_tx(1, TransactionTag::Deposit(1)),
-                    fake_tx(1, TransactionTag::Dispute),
+                    fake_tx(1, TransactionTag::Dispute(None)),
                 ),
                 (15000, 0, 15000, false),
             ),
@@ -110,9 +114,9 @@ fn account_verify_tx_reverse_hijack_by_reusing_id_impossible() {
                 vec!(
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     fake_tx(1, TransactionTag::Deposit(1)),
-                    fake_tx(1, TransactionTag::Dispute),
+                    fake_tx(1, TransactionTag::Dispute(None)),
                     fake_tx(2, TransactionTag::Withdrawal(15000)),
-                    fake_tx(1, TransactionTag::Chargeback),
+                    fake_tx(1, TransactionTag::Chargeback(None)),
                 ),
                 (0, 0, 0, true),
             ),
@@ -121,14 +125,14 @@ fn account_verify_tx_reverse_hijack_by_reusing_id_impossible() {
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     // will fail because tx with id 1 exists
                     fake_tx(1, TransactionTag::Deposit(1)),
-                    fake_tx(1, TransactionTag::Dispute),
+                    fake_tx(1, TransactionTag::Dispute(None)),
                     // will fail because tx is disputed
                     fake_tx(2, TransactionTag::Withdrawal(15000)),
-                    fake_tx(1, TransactionTag::Resolve),
+                    fake_tx(1, TransactionTag::Resolve(None)),
                     // will fail because tx with id 1 exists
                     fake_tx(1, TransactionTag::Deposit(1)),
-                    fake_tx(1, TransactionTag::Dispute),
-                    fake_tx(1, TransactionTag::Chargeback),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Chargeback(None)),
                     // everything below will fail because account is locked
                     fake_tx(3, TransactionTag::Deposit(20000)),
                     fake_tx(4, TransactionTag::Deposit(20000)),
@@ -169,7 +173,7 @@ fn account_basic_dispute_referencing_older_tx_sanity_check() {
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     fake_tx(2, TransactionTag::Deposit(15000)),
                     fake_tx(3, TransactionTag::Deposit(15000)),
-                    fake_tx(1, TransactionTag::Dispute),
+                    fake_tx(1, TransactionTag::Dispute(None)),
                 ),
                 (45000, 30000, 15000, false),
             ),
@@ -186,7 +190,7 @@ fn account_verify_chargeback_execution_order_respect() {
             (
                 vec!(
                     fake_tx(1, TransactionTag::Deposit(15000)),
-                    fake_tx(1, TransactionTag::Chargeback),
+                    fake_tx(1, TransactionTag::Chargeback(None)),
                 ),
                 (15000, 15000, 0, false),
             ),
@@ -203,8 +207,8 @@ fn account_verify_resolve_dispute_sanity() {
                 vec!(
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     fake_tx(2, TransactionTag::Deposit(15000)),
-                    fake_tx(1, TransactionTag::Dispute),
-                    fake_tx(1, TransactionTag::Resolve),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Resolve(None)),
                 ),
                 (30000, 30000, 0, false),
             ),
@@ -221,14 +225,14 @@ fn account_dispute_fuzz() {
                 vec!(
                     fake_tx(1, TransactionTag::Deposit(15000)),
                     fake_tx(2, TransactionTag::Deposit(15000)),
-                    fake_tx(1, TransactionTag::Resolve),
-                    fake_tx(1, TransactionTag::Dispute),
-                    fake_tx(1, TransactionTag::Dispute),
-                    fake_tx(1, TransactionTag::Resolve),
-                    fake_tx(1, TransactionTag::Resolve),
+                    fake_tx(1, TransactionTag::Resolve(None)),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Resolve(None)),
+                    fake_tx(1, TransactionTag::Resolve(None)),
                     fake_tx(3, TransactionTag::Deposit(15000)),
-                    fake_tx(1, TransactionTag::Dispute),
-                    fake_tx(1, TransactionTag::Resolve),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Resolve(None)),
                 ),
                 (45000, 45000, 0, false),
             )
@@ -1,19 +1,22 @@
 use simledger::account::Account;
 use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
 use simledger::traits::execution::TransactionExecution;
 use simledger::transaction::{Transaction, TransactionTag};
 
-static CLIENT_ID_A: u16 = 12;
-static CLIENT_ID_B: u16 = 21;
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
 
 fn fake_tx(
-    id: u32,
+    id: RawTxId,
     tag: TransactionTag,
 ) -> Transaction {
     Transaction {
         id,
         client_id: CLIENT_ID_A,
         tag,
+        timestamp: None,
+        asset: None,
     }
 }
 
@@ -67,23 +70,23 @@ fn account_verify_dispute_contract() {
                 Ok(ExecutionResult::NewAvailableBalance(15000)),
             ),
             (
-                fake_tx(1, TransactionTag::Dispute),
+                fake_tx(1, TransactionTag::Dispute(None)),
                 Ok(ExecutionResult::Ok),
             ),
             (
-                fake_tx(1, TransactionTag::Dispute),
+                fake_tx(1, TransactionTag::Dispute(None)),
                 Err(ExecutionError::InvalidTransaction),
             ),
             (
-                fake_tx(1, TransactionTag::Resolve),
+                fake_tx(1, TransactionTag::Resolve(None)),
                 Ok(ExecutionResult::Ok),
             ),
             (
-                fake_tx(1, TransactionTag::Dispute),
+                fake_tx(1, TransactionTag::Dispute(None)),
                 Ok(ExecutionResult::Ok),
             ),
             (
-                fake_tx(1, TransactionTag::Chargeback),
+                fake_tx(1, TransactionTag::Chargeback(None)),
                 Ok(ExecutionResult::Ok),
             ),
         ),
@@ -104,17 +107,17 @@ fn account_verify_dispute_resolve_chargeback_reject_withdrawal_tx() {
                 Ok(ExecutionResult::NewAvailableBalance(0)),
             ),
             (
-                fake_tx(2, TransactionTag::Dispute),
+                fake_tx(2, TransactionTag::Dispute(None)),
                 Err(ExecutionError::InvalidTransactionType),
             ),
             (
-                fake_tx(2, TransactionTag::Resolve),
+                fake_tx(2, TransactionTag::Resolve(None)),
                 // can't reach invalid type because tx needs
                 // to be in disputed book
                 Err(ExecutionError::InvalidTransaction),
             ),
             (
-                fake_tx(2, TransactionTag::Chargeback),
+                fake_tx(2, TransactionTag::Chargeback(None)),
                 // can't reach invalid type because tx needs
                 // to be in chargeback book
                 Err(ExecutionError::InvalidTransaction),
@@ -0,0 +1,160 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+// (available, credit used, credit remaining, deficit)
+type TestCasesCredit = Vec<(Vec<(Transaction, Result<ExecutionResult, ExecutionError>)>, (Amount, Amount, Amount, Amount))>;
+
+fn run_test_credit(cases: TestCasesCredit) {
+    for (txs, exp_balances) in cases.iter() {
+        let mut account =
+            Account::with_config(
+                CLIENT_ID_A,
+                AccountConfig {
+                    credit_limit: 10000,
+                    ..AccountConfig::default()
+                },
+            );
+
+        for (tx, exp_result) in txs.iter() {
+            assert_eq!(
+                &account.execute_transaction(tx),
+                exp_result,
+            );
+        }
+
+        assert_eq!(
+            (
+                account.amount_available(),
+                account.amount_credit_used(),
+                account.amount_credit_remaining(),
+                account.amount_deficit(),
+            ),
+            *exp_balances,
+        );
+    }
+}
+
+#[test]
+fn account_debit_within_credit_limit() {
+    run_test_credit(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(5000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(12000)),
+                        Ok(ExecutionResult::NewAvailableBalance(-7000)),
+                    ),
+                    (
+                        fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+                        Err(ExecutionError::InsufficientBalance),
+                    ),
+                    (
+                        fake_tx(4, CLIENT_ID_A, TransactionTag::Authorize(3000)),
+                        Ok(ExecutionResult::NewAvailableBalance(-10000)),
+                    ),
+                ),
+                (-10000, 10000, 0, 0),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::Withdrawal(4000)),
+                        Ok(ExecutionResult::NewAvailableBalance(-4000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+                        Ok(ExecutionResult::NewAvailableBalance(-3000)),
+                    ),
+                ),
+                (-3000, 3000, 7000, 0),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_adjust_credit_limit() {
+    run_test_credit(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(1, CLIENT_ID_A, TransactionTag::CreditLimit(20000)),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(15000)),
+                        Ok(ExecutionResult::NewAvailableBalance(-15000)),
+                    ),
+                    (
+                        fake_tx(3, CLIENT_ID_A, TransactionTag::CreditLimit(-1)),
+                        Err(ExecutionError::InvalidAmount),
+                    ),
+                    (
+                        // the overdraft beyond the new limit is owed right away
+                        fake_tx(4, CLIENT_ID_A, TransactionTag::CreditLimit(5000)),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                ),
+                (-15000, 5000, 0, 10000),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_credit_limit_per_client() {
+    let mut ledger = Ledger::new();
+
+    ledger.set_credit_limit(CLIENT_ID_A, 5000);
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+            Ok(ExecutionResult::NewAvailableBalance(-5000)),
+        ),
+        (
+            fake_tx(2, CLIENT_ID_B, TransactionTag::Withdrawal(5000)),
+            Err(ExecutionError::InsufficientBalance),
+        ),
+        (
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 1)),
+            Err(ExecutionError::InsufficientBalance),
+        ),
+    ).iter() {
+        assert_eq!(
+            &ledger.execute_transaction(tx),
+            exp_result,
+        );
+    }
+
+    assert_eq!(ledger.accounts()[&CLIENT_ID_A].amount_credit_used(), 5000);
+    assert_eq!(ledger.total_deficit(), Ok(0));
+}
@@ -0,0 +1,164 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::policy::ThresholdPolicy;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+type TestCasesDeficit = Vec<(Vec<Transaction>, (Amount, Amount, Amount))>;
+
+fn run_test_deficit(cases: TestCasesDeficit) {
+    for (txs, (exp_avail, exp_deficit, exp_lost)) in cases.iter() {
+        let mut account = Account::new(CLIENT_ID_A);
+
+        for tx in txs.iter() {
+            let _ = account.execute_transaction(tx);
+        }
+
+        assert_eq!(
+            (
+                account.amount_available(),
+                account.amount_deficit(),
+                account.amount_lost(),
+            ),
+            (*exp_avail, *exp_deficit, *exp_lost),
+        );
+    }
+}
+
+#[test]
+fn account_deficit_and_loss_after_chargeback() {
+    run_test_deficit(
+        vec!(
+            (
+                // withdrawn funds disputed, receivable but no loss yet
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(15000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                ),
+                (-15000, 15000, 0),
+            ),
+            (
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(15000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+                ),
+                (-15000, 15000, 15000),
+            ),
+            (
+                // only the uncovered part of the chargeback is lost
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+                    fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(12000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+                ),
+                (-7000, 7000, 7000),
+            ),
+            (
+                // resolved disputes leave no deficit behind
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(15000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Resolve(None)),
+                ),
+                (0, 0, 0),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_aggregates_deficit_and_loss() {
+    let mut ledger = Ledger::new();
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(15000)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+        fake_tx(3, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+        fake_tx(4, CLIENT_ID_B, TransactionTag::Withdrawal(2000)),
+        fake_tx(3, CLIENT_ID_B, TransactionTag::Dispute(None)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    assert_eq!(ledger.total_deficit(), Ok(17000));
+    assert_eq!(ledger.total_lost(), Ok(15000));
+}
+
+#[test]
+fn account_block_activity_while_in_deficit() {
+    let mut account = Account::with_policy(
+        CLIENT_ID_A,
+        AccountConfig::default(),
+        ThresholdPolicy {
+            block_on_deficit: true,
+            ..ThresholdPolicy::default()
+        },
+    );
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+        ),
+        (
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(10000)),
+            Ok(ExecutionResult::NewAvailableBalance(5000)),
+        ),
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+            Ok(ExecutionResult::Ok),
+        ),
+        (
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(1000)),
+            Err(ExecutionError::AccountInDeficit),
+        ),
+        (
+            // deposits pay the deficit back
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Deposit(20000)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+        (
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(1000)),
+            Ok(ExecutionResult::NewAvailableBalance(9000)),
+        ),
+        (
+            // administrative transactions are still processed
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Resolve(None)),
+            Ok(ExecutionResult::Ok),
+        ),
+    ).iter() {
+        assert_eq!(
+            &account.execute_transaction(tx),
+            exp_result,
+        );
+    }
+}
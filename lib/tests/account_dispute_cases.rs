@@ -0,0 +1,138 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::config::AccountConfig;
+use simledger::dispute::{DisputeCase, DisputeOutcome, DisputeState};
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn fake_case(
+    case_id: u32,
+    (amount, charged_back): (Amount, Amount),
+    (opened_seq, closed_seq): (u64, Option<u64>),
+    outcome: Option<DisputeOutcome>,
+    prior_disputes: u32,
+) -> DisputeCase {
+    DisputeCase {
+        case_id,
+
+        tx_id: 1,
+        asset: Asset::default(),
+        amount,
+        charged_back,
+
+        opened_seq,
+        closed_seq,
+
+        state: match outcome {
+            None => DisputeState::Open,
+            Some(_) => DisputeState::Closed,
+        },
+        outcome,
+
+        prior_disputes,
+    }
+}
+
+#[test]
+fn account_dispute_case_lifecycle() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for tx in vec!(
+        fake_tx(1, TransactionTag::Deposit(15000)),
+        fake_tx(1, TransactionTag::Dispute(None)),
+        fake_tx(1, TransactionTag::Resolve(None)),
+        fake_tx(1, TransactionTag::Dispute(Some(5000))),
+        fake_tx(1, TransactionTag::Resolve(Some(2000))),
+        fake_tx(1, TransactionTag::Chargeback(None)),
+    ).iter() {
+        account.execute_transaction(tx).unwrap();
+    }
+
+    assert_eq!(
+        account.dispute_cases(),
+        &[
+            fake_case(1, (15000, 0), (2, Some(3)), Some(DisputeOutcome::Resolved), 0),
+            fake_case(2, (5000, 3000), (4, Some(6)), Some(DisputeOutcome::ChargedBack), 1),
+        ],
+    );
+
+    assert_eq!(account.dispute_case(0), None);
+    assert_eq!(account.dispute_case(3), None);
+}
+
+#[test]
+fn account_reject_disputes_beyond_limit() {
+    let mut account = Account::with_config(
+        CLIENT_ID_A,
+        AccountConfig {
+            max_disputes: Some(2),
+            ..AccountConfig::default()
+        },
+    );
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, TransactionTag::Deposit(15000)),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+        ),
+        (fake_tx(1, TransactionTag::Dispute(None)), Ok(ExecutionResult::Ok)),
+        (fake_tx(1, TransactionTag::Resolve(None)), Ok(ExecutionResult::Ok)),
+        (fake_tx(1, TransactionTag::Dispute(None)), Ok(ExecutionResult::Ok)),
+        (fake_tx(1, TransactionTag::Resolve(None)), Ok(ExecutionResult::Ok)),
+        (
+            fake_tx(1, TransactionTag::Dispute(None)),
+            Err(ExecutionError::DisputeLimitReached),
+        ),
+    ).iter() {
+        assert_eq!(
+            &account.execute_transaction(tx),
+            exp_result,
+        );
+    }
+
+    assert_eq!(account.dispute_cases().len(), 2);
+    assert_eq!(account.amount_available(), 15000);
+}
+
+#[test]
+fn ledger_rollback_restores_dispute_cases() {
+    let mut ledger = Ledger::new();
+
+    ledger.execute_transaction(&fake_tx(1, TransactionTag::Deposit(15000))).unwrap();
+    ledger.execute_transaction(&fake_tx(1, TransactionTag::Dispute(None))).unwrap();
+
+    let savepoint = ledger.savepoint();
+
+    ledger.execute_transaction(&fake_tx(1, TransactionTag::Resolve(None))).unwrap();
+    ledger.execute_transaction(&fake_tx(1, TransactionTag::Dispute(None))).unwrap();
+
+    ledger.rollback_to(savepoint).unwrap();
+
+    let account = ledger.accounts().get(&CLIENT_ID_A).unwrap();
+
+    assert_eq!(
+        account.dispute_cases(),
+        &[fake_case(1, (15000, 0), (2, None), None, 0)],
+    );
+    assert_eq!(account.amount_held(), 15000);
+}
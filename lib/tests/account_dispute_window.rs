@@ -0,0 +1,111 @@
+use simledger::account::Account;
+use simledger::config::{AccountConfig, DisputeWindow};
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+// 90 days
+static DISPUTE_WINDOW: u64 = 90 * 24 * 60 * 60;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+    timestamp: Option<u64>,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp,
+        asset: None,
+    }
+}
+
+type TestCases = Vec<(Transaction, Result<ExecutionResult, ExecutionError>)>;
+
+fn run_test_contract(config: AccountConfig, cases: TestCases) {
+    let mut account = Account::with_config(
+        CLIENT_ID_A,
+        config,
+    );
+
+    for (tx, exp_result) in cases.iter() {
+        assert_eq!(
+            &account.execute_transaction(&tx),
+            exp_result,
+        );
+    }
+}
+
+#[test]
+fn account_reject_dispute_outside_of_window() {
+    run_test_contract(
+        AccountConfig {
+            dispute_window: DisputeWindow::Seconds(DISPUTE_WINDOW),
+            ..AccountConfig::default()
+        },
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000), Some(1_000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+            ),
+            (
+                fake_tx(2, TransactionTag::Deposit(15000), Some(2_000)),
+                Ok(ExecutionResult::NewAvailableBalance(30000)),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None), Some(1_000 + DISPUTE_WINDOW + 1)),
+                Err(ExecutionError::DisputeWindowExpired),
+            ),
+            (
+                fake_tx(2, TransactionTag::Dispute(None), Some(1_000 + DISPUTE_WINDOW + 1)),
+                Ok(ExecutionResult::Ok),
+            ),
+            (
+                // undated disputes can't be checked against the window
+                fake_tx(1, TransactionTag::Dispute(None), None),
+                Ok(ExecutionResult::Ok),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_accept_dispute_of_undated_deposit() {
+    run_test_contract(
+        AccountConfig {
+            dispute_window: DisputeWindow::Seconds(DISPUTE_WINDOW),
+            ..AccountConfig::default()
+        },
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000), None),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None), Some(1_000 + DISPUTE_WINDOW + 1)),
+                Ok(ExecutionResult::Ok),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_unlimited_dispute_window_by_default() {
+    run_test_contract(
+        AccountConfig::default(),
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000), Some(1_000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None), Some(1_000 + 10 * DISPUTE_WINDOW)),
+                Ok(ExecutionResult::Ok),
+            ),
+        ),
+    );
+}
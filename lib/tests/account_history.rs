@@ -0,0 +1,154 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::history::AccountBalance;
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn balance(
+    (total, available, held, locked): (Amount, Amount, Amount, bool),
+) -> AccountBalance {
+    AccountBalance {
+        available,
+        held,
+        total,
+        locked,
+
+        held_disputed: held,
+        held_authorized: 0,
+
+        deficit: 0,
+        lost: 0,
+
+        credit_limit: 0,
+        credit_used: 0,
+    }
+}
+
+#[test]
+fn account_history_only_records_applied_transactions() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        // will fail because tx with id 1 exists
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(1)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Resolve(None)),
+    ).iter() {
+        let _ = account.execute_transaction(tx);
+    }
+
+    let entries: Vec<(u64, TransactionTag, Amount, Amount, Amount)> =
+        account.history()
+            .iter()
+            .map(|entry| (
+                entry.seq,
+                entry.tx.tag,
+                entry.amount,
+                entry.balance.available,
+                entry.balance.held,
+            ))
+            .collect();
+
+    assert_eq!(
+        entries,
+        vec!(
+            (1, TransactionTag::Deposit(15000), 15000, 15000, 0),
+            (2, TransactionTag::Dispute(None), 15000, 0, 15000),
+            (3, TransactionTag::Resolve(None), 15000, 15000, 0),
+        ),
+    );
+}
+
+#[test]
+fn account_history_keeps_entries_moved_between_books() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(2500)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+    ).iter() {
+        account.execute_transaction(tx).unwrap();
+    }
+
+    let entries: Vec<(RawTxId, Amount, AccountBalance)> =
+        account.history()
+            .iter()
+            .map(|entry| (entry.tx.id, entry.amount, entry.balance))
+            .collect();
+
+    assert_eq!(
+        entries,
+        vec!(
+            (1, 15000, balance((15000, 15000, 0, false))),
+            (2, 5000, balance((20000, 20000, 0, false))),
+            (3, 2500, balance((17500, 17500, 0, false))),
+            (1, 15000, balance((17500, 2500, 15000, false))),
+            (1, 15000, balance((2500, 2500, 0, true))),
+        ),
+    );
+}
+
+#[test]
+fn ledger_balance_at_sequence() {
+    let mut ledger = Ledger::new();
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        // will fail because of insufficient balance
+        fake_tx(4, CLIENT_ID_A, TransactionTag::Withdrawal(10000)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+    ).iter() {
+        let _ = ledger.execute_transaction(tx);
+    }
+
+    assert_eq!(ledger.sequence(), 6);
+
+    for (seq, exp_balance) in vec!(
+        (0, (0, 0, 0, false)),
+        (1, (15000, 15000, 0, false)),
+        (2, (15000, 15000, 0, false)),
+        (3, (20000, 20000, 0, false)),
+        (4, (20000, 5000, 15000, false)),
+        (5, (20000, 5000, 15000, false)),
+        (6, (5000, 5000, 0, true)),
+        (100, (5000, 5000, 0, true)),
+    ).into_iter() {
+        assert_eq!(
+            ledger.balance_at(CLIENT_ID_A, seq),
+            Ok(Some(balance(exp_balance))),
+        );
+    }
+
+    assert_eq!(
+        ledger.balance_at(CLIENT_ID_B, 1),
+        Ok(Some(balance((0, 0, 0, false)))),
+    );
+
+    assert_eq!(ledger.balance_at(42, 6), Ok(None));
+}
@@ -0,0 +1,117 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::fee::Fee;
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+#[test]
+fn account_rejects_overflowing_balances() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for (tx, exp_result, exp_available) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(Amount::MAX)),
+            Ok(ExecutionResult::NewAvailableBalance(Amount::MAX)),
+            Amount::MAX,
+        ),
+        (
+            // nothing is booked for a rejected deposit
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(1)),
+            Err(ExecutionError::Overflow),
+            Amount::MAX,
+        ),
+        (
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(Amount::MAX)),
+            Ok(ExecutionResult::NewAvailableBalance(0)),
+            0,
+        ),
+        (
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Deposit(Amount::MAX)),
+            Ok(ExecutionResult::NewAvailableBalance(Amount::MAX)),
+            Amount::MAX,
+        ),
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+            Ok(ExecutionResult::Ok),
+            0,
+        ),
+        (
+            // would hold more than an amount can represent
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Dispute(None)),
+            Err(ExecutionError::Overflow),
+            0,
+        ),
+        (
+            // as would the total balance
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Deposit(1)),
+            Err(ExecutionError::Overflow),
+            0,
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+        assert_eq!(account.amount_available(), exp_available);
+    }
+
+    assert!(!account.book.contains_key(&2));
+    assert!(!account.book.contains_key(&5));
+    assert!(!account.book_disputed.contains_key(&4));
+    assert_eq!(account.history().len(), 4);
+    assert_eq!(account.dispute_cases().len(), 1);
+
+    assert_eq!(account.amount_held(), Amount::MAX);
+    assert_eq!(account.amount_total(), Amount::MAX);
+}
+
+#[test]
+fn ledger_rolls_back_overflowing_transfers() {
+    let mut ledger = Ledger::new();
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(Amount::MAX)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    assert_eq!(
+        ledger.execute_transaction(&fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000))),
+        Err(ExecutionError::Overflow),
+    );
+
+    assert_eq!(ledger.accounts()[&CLIENT_ID_A].amount_available(), 10000);
+    assert_eq!(ledger.accounts()[&CLIENT_ID_B].amount_available(), Amount::MAX);
+
+    // totals across accounts saturate as well
+    assert_eq!(ledger.amount_total(), Ok(Amount::MAX));
+}
+
+#[test]
+fn fee_amounts_saturate() {
+    for (fee, amount, exp_fee) in vec!(
+        (Fee::percentage(100), 10000, 100),
+        (Fee::percentage(10000), Amount::MAX, Amount::MAX),
+        (Fee { flat: Amount::MAX, basis_points: 100 }, 10000, Amount::MAX),
+    ).into_iter() {
+        assert_eq!(fee.amount_for(amount), exp_fee);
+    }
+}
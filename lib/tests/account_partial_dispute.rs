@@ -0,0 +1,143 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+type TestCases = Vec<(Transaction, Result<ExecutionResult, ExecutionError>, (Amount, Amount, Amount, bool))>;
+
+fn run_test_contract(cases: TestCases) {
+    let mut account = Account::new(
+        CLIENT_ID_A,
+    );
+
+    for (tx, exp_result, (exp_total, exp_avail, exp_held, exp_locked)) in cases.iter() {
+        assert_eq!(
+            &account.execute_transaction(&tx),
+            exp_result,
+        );
+
+        assert_eq!(
+            (
+                account.amount_total(),
+                account.amount_available(),
+                account.amount_held(),
+                account.locked(),
+            ),
+            (*exp_total, *exp_avail, *exp_held, *exp_locked),
+        );
+    }
+}
+
+#[test]
+fn account_partial_dispute_and_resolve() {
+    run_test_contract(
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(Some(15001))),
+                Err(ExecutionError::InvalidAmount),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(Some(0))),
+                Err(ExecutionError::InvalidAmount),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(Some(5000))),
+                Ok(ExecutionResult::Ok),
+                (15000, 10000, 5000, false),
+            ),
+            (
+                // only one dispute may be open per deposit
+                fake_tx(1, TransactionTag::Dispute(Some(5000))),
+                Err(ExecutionError::InvalidTransaction),
+                (15000, 10000, 5000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Resolve(Some(2000))),
+                Ok(ExecutionResult::Ok),
+                (15000, 12000, 3000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Resolve(Some(3001))),
+                Err(ExecutionError::InvalidAmount),
+                (15000, 12000, 3000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Resolve(None)),
+                Ok(ExecutionResult::Ok),
+                (15000, 15000, 0, false),
+            ),
+            (
+                // fully resolved, back in the book
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (15000, 0, 15000, false),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_partial_chargeback_releases_remainder() {
+    run_test_contract(
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Deposit(5000)),
+                Ok(ExecutionResult::NewAvailableBalance(20000)),
+                (20000, 20000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(Some(10000))),
+                Ok(ExecutionResult::Ok),
+                (20000, 10000, 10000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Chargeback(Some(4000))),
+                Ok(ExecutionResult::Ok),
+                (16000, 16000, 0, true),
+            ),
+        ),
+    );
+
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for tx in vec!(
+        fake_tx(1, TransactionTag::Deposit(15000)),
+        fake_tx(1, TransactionTag::Dispute(Some(10000))),
+        fake_tx(1, TransactionTag::Chargeback(Some(4000))),
+    ).iter() {
+        account.execute_transaction(tx).unwrap();
+    }
+
+    let entry = account.book_chargeback.get(&1).unwrap();
+
+    assert_eq!((entry.disputed, entry.charged_back), (0, 4000));
+}
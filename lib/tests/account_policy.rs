@@ -0,0 +1,171 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::policy::ThresholdPolicy;
+use simledger::traits::execution::TransactionExecution;
+use simledger::traits::policy::AccountPolicy;
+use simledger::transaction::{LedgerBookEntry, Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+type TestCases = Vec<(Transaction, Result<ExecutionResult, ExecutionError>, (Amount, Amount, Amount, bool))>;
+
+fn run_test_contract<P: AccountPolicy>(policy: P, cases: TestCases) {
+    let mut account = Account::with_policy(
+        CLIENT_ID_A,
+        AccountConfig::default(),
+        policy,
+    );
+
+    for (tx, exp_result, (exp_total, exp_avail, exp_held, exp_locked)) in cases.iter() {
+        assert_eq!(
+            &account.execute_transaction(&tx),
+            exp_result,
+        );
+
+        assert_eq!(
+            (
+                account.amount_total(),
+                account.amount_available(),
+                account.amount_held(),
+                account.locked(),
+            ),
+            (*exp_total, *exp_avail, *exp_held, *exp_locked),
+        );
+    }
+}
+
+// disputes anything and never locks, to verify the policy is consulted
+#[derive(Clone)]
+struct LenientPolicy;
+
+impl AccountPolicy for LenientPolicy {
+    fn assert_is_disputable(
+        &self,
+        _account: &Account<Self>,
+        _subject_tx: &LedgerBookEntry,
+        _amount: Amount,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        Ok(ExecutionResult::Ok)
+    }
+
+    fn lock_on_chargeback(
+        &self,
+        _account: &Account<Self>,
+    ) -> bool {
+        false
+    }
+}
+
+#[test]
+fn account_threshold_policy_locks_after_n_chargebacks() {
+    run_test_contract(
+        ThresholdPolicy {
+            lock_after_chargebacks: 2,
+            ..ThresholdPolicy::default()
+        },
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Deposit(5000)),
+                Ok(ExecutionResult::NewAvailableBalance(20000)),
+                (20000, 20000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (20000, 5000, 15000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                (5000, 5000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (5000, 0, 5000, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                (0, 0, 0, true),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_threshold_policy_forbids_negative_disputes() {
+    run_test_contract(
+        ThresholdPolicy {
+            allow_negative_available: false,
+            ..ThresholdPolicy::default()
+        },
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Withdrawal(10000)),
+                Ok(ExecutionResult::NewAvailableBalance(5000)),
+                (5000, 5000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Err(ExecutionError::DisputeExceedsAvailable),
+                (5000, 5000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(Some(5000))),
+                Ok(ExecutionResult::Ok),
+                (5000, 0, 5000, false),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_custom_policy_is_consulted() {
+    run_test_contract(
+        LenientPolicy,
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (15000, 0, 15000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                (0, 0, 0, false),
+            ),
+        ),
+    );
+}
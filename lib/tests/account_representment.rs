@@ -0,0 +1,203 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::config::AccountConfig;
+use simledger::dispute::DisputeOutcome;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+type TestCases = Vec<(Transaction, Result<ExecutionResult, ExecutionError>, (Amount, Amount, Amount, bool))>;
+
+fn run_test_contract(config: AccountConfig, cases: TestCases) -> Account {
+    let mut account = Account::with_config(
+        CLIENT_ID_A,
+        config,
+    );
+
+    for (tx, exp_result, (exp_total, exp_avail, exp_held, exp_locked)) in cases.iter() {
+        assert_eq!(
+            &account.execute_transaction(&tx),
+            exp_result,
+        );
+
+        assert_eq!(
+            (
+                account.amount_total(),
+                account.amount_available(),
+                account.amount_held(),
+                account.locked(),
+            ),
+            (*exp_total, *exp_avail, *exp_held, *exp_locked),
+        );
+    }
+
+    account
+}
+
+#[test]
+fn account_representment_and_second_chargeback() {
+    let account = run_test_contract(
+        AccountConfig::default(),
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Deposit(5000)),
+                Ok(ExecutionResult::NewAvailableBalance(20000)),
+                (20000, 20000, 0, false),
+            ),
+            (
+                // can't represent what has not been charged back
+                fake_tx(1, TransactionTag::Representment),
+                Err(ExecutionError::InvalidTransaction),
+                (20000, 20000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (20000, 5000, 15000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Chargeback(Some(10000))),
+                Ok(ExecutionResult::Ok),
+                (10000, 10000, 0, true),
+            ),
+            (
+                fake_tx(1, TransactionTag::Representment),
+                Ok(ExecutionResult::Ok),
+                (20000, 20000, 0, true),
+            ),
+            (
+                fake_tx(1, TransactionTag::Representment),
+                Err(ExecutionError::InvalidTransaction),
+                (20000, 20000, 0, true),
+            ),
+            (
+                fake_tx(1, TransactionTag::SecondChargeback),
+                Ok(ExecutionResult::Ok),
+                (10000, 10000, 0, true),
+            ),
+            (
+                fake_tx(1, TransactionTag::SecondChargeback),
+                Err(ExecutionError::InvalidTransaction),
+                (10000, 10000, 0, true),
+            ),
+        ),
+    );
+
+    let case = account.dispute_case(1).unwrap();
+
+    assert_eq!(case.outcome, Some(DisputeOutcome::SecondChargedBack));
+    assert_eq!(case.charged_back, 10000);
+}
+
+#[test]
+fn account_second_chargeback_requires_representment() {
+    run_test_contract(
+        AccountConfig::default(),
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::SecondChargeback),
+                Err(ExecutionError::InvalidTransaction),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (15000, 0, 15000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Resolve(None)),
+                Ok(ExecutionResult::Ok),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::SecondChargeback),
+                Err(ExecutionError::InvalidTransaction),
+                (15000, 15000, 0, false),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_unlock_on_representment() {
+    run_test_contract(
+        AccountConfig {
+            unlock_on_representment: true,
+            ..AccountConfig::default()
+        },
+        vec!(
+            (
+                fake_tx(1, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                (15000, 15000, 0, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Deposit(5000)),
+                Ok(ExecutionResult::NewAvailableBalance(20000)),
+                (20000, 20000, 0, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (20000, 5000, 15000, false),
+            ),
+            (
+                fake_tx(2, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                (20000, 0, 20000, false),
+            ),
+            (
+                fake_tx(1, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                (5000, 0, 5000, true),
+            ),
+            (
+                fake_tx(2, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                (0, 0, 0, true),
+            ),
+            (
+                // chargeback of tx 2 is still standing
+                fake_tx(1, TransactionTag::Representment),
+                Ok(ExecutionResult::Ok),
+                (15000, 15000, 0, true),
+            ),
+            (
+                fake_tx(2, TransactionTag::Representment),
+                Ok(ExecutionResult::Ok),
+                (20000, 20000, 0, false),
+            ),
+            (
+                fake_tx(3, TransactionTag::Withdrawal(20000)),
+                Ok(ExecutionResult::NewAvailableBalance(0)),
+                (0, 0, 0, false),
+            ),
+        ),
+    );
+}
@@ -0,0 +1,146 @@
+use simledger::account::Account;
+use simledger::asset::Asset;
+use simledger::id::{RawClientId, RawTxId};
+use simledger::precision::{AssetScales, InputRounding};
+use simledger::risk::{score_account, RiskConfig, RiskSignal};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+// rows of a type,client,tx,amount file, all for the same client
+fn csv_txs(text: &str) -> Vec<Transaction> {
+    let scales = AssetScales::default();
+
+    text.lines()
+        .skip(1)
+        .map(|line| {
+            let columns: Vec<&str> = line.split(',').collect();
+
+            let id = columns[2].parse().unwrap();
+            let amount = || {
+                scales.parse_amount(Asset::default(), columns[3], InputRounding::Reject)
+                    .unwrap()
+                    .amount
+            };
+
+            let tag =
+                match columns[0] {
+                    "deposit" => TransactionTag::Deposit(amount()),
+                    "withdrawal" => TransactionTag::Withdrawal(amount()),
+                    "dispute" => TransactionTag::Dispute(None),
+                    "chargeback" => TransactionTag::Chargeback(None),
+                    tx_type => panic!("unexpected transaction type {}", tx_type),
+                };
+
+            fake_tx(id, tag)
+        })
+        .collect()
+}
+
+type TestCasesRisk = Vec<(Vec<Transaction>, (u32, bool, Vec<RiskSignal>))>;
+
+fn run_test_risk(cases: TestCasesRisk) {
+    for (txs, (exp_score, exp_flagged, exp_signals)) in cases.iter() {
+        let mut account = Account::new(CLIENT_ID_A);
+
+        for tx in txs.iter() {
+            let _ = account.execute_transaction(tx);
+        }
+
+        let risk = score_account(&account, &RiskConfig::default());
+
+        assert_eq!(
+            (risk.score, risk.flagged, &risk.signals),
+            (*exp_score, *exp_flagged, exp_signals),
+        );
+    }
+}
+
+#[test]
+fn account_risk_scoring() {
+    let hijack = csv_txs(include_str!("../../data/test_prevent_id_hijack.csv"));
+
+    run_test_risk(
+        vec!(
+            (
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Deposit(15000)),
+                    fake_tx(3, TransactionTag::Withdrawal(5000)),
+                ),
+                (0, false, vec!()),
+            ),
+            (
+                // the attack is flagged before it is charged back
+                hijack[..4].to_vec(),
+                (
+                    70,
+                    true,
+                    vec!(
+                        RiskSignal::HighDisputeRatio,
+                        RiskSignal::DuplicateIdAttempts(1),
+                        RiskSignal::HeldFundsWithdrawals(1),
+                    ),
+                ),
+            ),
+            (
+                hijack.clone(),
+                (
+                    95,
+                    true,
+                    vec!(
+                        RiskSignal::HighDisputeRatio,
+                        RiskSignal::Chargebacks(1),
+                        RiskSignal::DuplicateIdAttempts(1),
+                        RiskSignal::HeldFundsWithdrawals(1),
+                    ),
+                ),
+            ),
+            (
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(2, TransactionTag::Deposit(1000)),
+                    fake_tx(3, TransactionTag::Withdrawal(10000)),
+                    fake_tx(4, TransactionTag::Deposit(20000)),
+                    fake_tx(5, TransactionTag::Deposit(1000)),
+                    fake_tx(6, TransactionTag::Deposit(1000)),
+                    fake_tx(7, TransactionTag::Withdrawal(10000)),
+                ),
+                (40, false, vec!(RiskSignal::RapidWithdrawals(2))),
+            ),
+            (
+                // the id of a disputed deposit is replayed too
+                vec!(
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(1, TransactionTag::Dispute(None)),
+                    fake_tx(1, TransactionTag::Deposit(15000)),
+                    fake_tx(1, TransactionTag::Withdrawal(5000)),
+                    fake_tx(1, TransactionTag::Chargeback(None)),
+                ),
+                (
+                    85,
+                    true,
+                    vec!(
+                        RiskSignal::HighDisputeRatio,
+                        RiskSignal::Chargebacks(1),
+                        RiskSignal::DuplicateIdAttempts(2),
+                    ),
+                ),
+            ),
+        ),
+    );
+}
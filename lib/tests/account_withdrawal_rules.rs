@@ -0,0 +1,277 @@
+use simledger::account::Account;
+use simledger::asset::Asset;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::fx::{FxTable, Rounding};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::rules::{VelocityLimit, WithdrawalRules};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+    timestamp: Option<u64>,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp,
+        asset: None,
+    }
+}
+
+type TestCasesRules = Vec<(Transaction, Result<ExecutionResult, ExecutionError>)>;
+
+fn run_test_rules(withdrawal_rules: WithdrawalRules, cases: TestCasesRules) {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                withdrawal_rules,
+                ..AccountConfig::default()
+            },
+        );
+
+    account.execute_transaction(
+        &fake_tx(1, TransactionTag::Deposit(1_000_000), None),
+    ).unwrap();
+
+    for (tx, exp_result) in cases.iter() {
+        let result = account.execute_transaction(tx);
+
+        assert_eq!(&result, exp_result);
+        assert_eq!(
+            result.err().filter(ExecutionError::is_rule_violation).is_some(),
+            exp_result.is_err(),
+        );
+    }
+}
+
+#[test]
+fn account_max_withdrawal() {
+    run_test_rules(
+        WithdrawalRules {
+            max_withdrawal: Some(50000),
+            ..WithdrawalRules::default()
+        },
+        vec!(
+            (
+                fake_tx(2, TransactionTag::Withdrawal(50000), None),
+                Ok(ExecutionResult::NewAvailableBalance(950000)),
+            ),
+            (
+                fake_tx(3, TransactionTag::Withdrawal(50001), None),
+                Err(ExecutionError::WithdrawalLimitExceeded),
+            ),
+            (
+                // deposits are not limited
+                fake_tx(4, TransactionTag::Deposit(100000), None),
+                Ok(ExecutionResult::NewAvailableBalance(1050000)),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_velocity_per_transactions() {
+    run_test_rules(
+        WithdrawalRules {
+            velocity_per_transactions: Some(VelocityLimit { max_withdrawals: 2, per: 3 }),
+            ..WithdrawalRules::default()
+        },
+        vec!(
+            (
+                fake_tx(2, TransactionTag::Withdrawal(1000), None),
+                Ok(ExecutionResult::NewAvailableBalance(999000)),
+            ),
+            (
+                fake_tx(3, TransactionTag::Withdrawal(1000), None),
+                Ok(ExecutionResult::NewAvailableBalance(998000)),
+            ),
+            (
+                fake_tx(4, TransactionTag::Withdrawal(1000), None),
+                Err(ExecutionError::VelocityLimitExceeded),
+            ),
+            (
+                fake_tx(5, TransactionTag::Deposit(1000), None),
+                Ok(ExecutionResult::NewAvailableBalance(999000)),
+            ),
+            (
+                fake_tx(6, TransactionTag::Withdrawal(1000), None),
+                Ok(ExecutionResult::NewAvailableBalance(998000)),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_velocity_per_seconds() {
+    run_test_rules(
+        WithdrawalRules {
+            velocity_per_seconds: Some(VelocityLimit { max_withdrawals: 2, per: 60 }),
+            ..WithdrawalRules::default()
+        },
+        vec!(
+            (
+                fake_tx(2, TransactionTag::Withdrawal(1000), Some(1000)),
+                Ok(ExecutionResult::NewAvailableBalance(999000)),
+            ),
+            (
+                fake_tx(3, TransactionTag::Withdrawal(1000), Some(1030)),
+                Ok(ExecutionResult::NewAvailableBalance(998000)),
+            ),
+            (
+                fake_tx(4, TransactionTag::Withdrawal(1000), Some(1059)),
+                Err(ExecutionError::VelocityWindowExceeded),
+            ),
+            (
+                // time based rules need a timestamp
+                fake_tx(5, TransactionTag::Withdrawal(1000), None),
+                Ok(ExecutionResult::NewAvailableBalance(997000)),
+            ),
+            (
+                fake_tx(6, TransactionTag::Withdrawal(1000), Some(1060)),
+                Ok(ExecutionResult::NewAvailableBalance(996000)),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_daily_withdrawal_cap() {
+    run_test_rules(
+        WithdrawalRules {
+            daily_withdrawal_cap: Some(100000),
+            ..WithdrawalRules::default()
+        },
+        vec!(
+            (
+                fake_tx(2, TransactionTag::Withdrawal(60000), Some(86_400)),
+                Ok(ExecutionResult::NewAvailableBalance(940000)),
+            ),
+            (
+                fake_tx(3, TransactionTag::Withdrawal(50000), Some(90_000)),
+                Err(ExecutionError::DailyCapExceeded),
+            ),
+            (
+                fake_tx(4, TransactionTag::Withdrawal(40000), Some(90_000)),
+                Ok(ExecutionResult::NewAvailableBalance(900000)),
+            ),
+            (
+                fake_tx(5, TransactionTag::Withdrawal(50000), Some(172_800)),
+                Ok(ExecutionResult::NewAvailableBalance(850000)),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn account_rejects_duplicates_before_rules() {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                withdrawal_rules: WithdrawalRules {
+                    max_withdrawal: Some(50000),
+                    ..WithdrawalRules::default()
+                },
+                ..AccountConfig::default()
+            },
+        );
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, TransactionTag::Deposit(1_000_000), None),
+            Ok(ExecutionResult::NewAvailableBalance(1_000_000)),
+        ),
+        (
+            fake_tx(2, TransactionTag::Withdrawal(50000), None),
+            Ok(ExecutionResult::NewAvailableBalance(950000)),
+        ),
+        (
+            // a replayed id is reported as such, whatever the rules say
+            fake_tx(2, TransactionTag::Withdrawal(50001), None),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(1, TransactionTag::Withdrawal(60000), None),
+            Err(ExecutionError::TransactionExists),
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+    }
+}
+
+#[test]
+fn ledger_limits_withdrawals_in_other_assets() {
+    let eur = Asset::new("EUR").unwrap();
+
+    let mut fx_table = FxTable::new(Rounding::HalfEven);
+    assert!(fx_table.add_rate(eur, Asset::default(), 200_000_000, 0).is_ok());
+
+    for (fx_table, txs) in vec!(
+        (
+            Some(fx_table),
+            vec!(
+                (
+                    // worth 60000 in the base asset
+                    fake_tx(2, TransactionTag::Withdrawal(30000), Some(86_400)),
+                    Ok(ExecutionResult::NewAvailableBalance(970000)),
+                ),
+                (
+                    fake_tx(3, TransactionTag::Withdrawal(60000), Some(90_000)),
+                    Err(ExecutionError::WithdrawalLimitExceeded),
+                ),
+                (
+                    fake_tx(4, TransactionTag::Withdrawal(25000), Some(90_000)),
+                    Err(ExecutionError::DailyCapExceeded),
+                ),
+                (
+                    fake_tx(5, TransactionTag::Withdrawal(20000), Some(90_000)),
+                    Ok(ExecutionResult::NewAvailableBalance(950000)),
+                ),
+            ),
+        ),
+        (
+            None,
+            vec!(
+                (
+                    fake_tx(2, TransactionTag::Withdrawal(100), Some(86_400)),
+                    Err(ExecutionError::RateUnavailable),
+                ),
+            ),
+        ),
+    ).into_iter() {
+        let mut ledger =
+            Ledger::with_config(
+                AccountConfig {
+                    withdrawal_rules: WithdrawalRules {
+                        max_withdrawal: Some(100000),
+                        daily_withdrawal_cap: Some(100000),
+                        ..WithdrawalRules::default()
+                    },
+                    ..AccountConfig::default()
+                },
+            );
+
+        if let Some(fx_table) = fx_table {
+            ledger.set_fx_table(fx_table);
+        }
+
+        let mut deposit = fake_tx(1, TransactionTag::Deposit(1_000_000), None);
+        deposit.asset = Some(eur);
+
+        assert!(ledger.execute_transaction(&deposit).is_ok());
+
+        for (mut tx, exp_result) in txs.into_iter() {
+            tx.asset = Some(eur);
+
+            assert_eq!(ledger.execute_transaction(&tx), exp_result);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::execution::ExecutionError;
+use simledger::fx::{FxTable, Rounding};
+use simledger::precision::{AssetScales, InputRounding, ParsedAmount};
+
+fn asset(code: &str) -> Asset {
+    Asset::new(code).unwrap()
+}
+
+fn scales() -> AssetScales {
+    let mut scales = AssetScales::default();
+
+    assert!(scales.set_decimals(asset("JPY"), 0).is_ok());
+    assert!(scales.set_decimals(asset("BTC"), 8).is_ok());
+
+    scales
+}
+
+fn parsed(amount: Amount, rounded: bool) -> Result<ParsedAmount, ExecutionError> {
+    Ok(ParsedAmount { amount, rounded })
+}
+
+#[test]
+fn asset_scales() {
+    let scales = scales();
+
+    assert_eq!(scales.decimals(asset("USD")), 4);
+    assert_eq!(scales.scale(asset("JPY")), 1);
+    assert_eq!(scales.scale(asset("BTC")), 100_000_000);
+
+    assert_eq!(
+        AssetScales::default().set_decimals(asset("USD"), 19),
+        Err(ExecutionError::InvalidAmount),
+    );
+}
+
+#[test]
+fn amount_parsing() {
+    let scales = scales();
+
+    for (code, text, rounding, exp_parsed) in vec!(
+        ("USD", "2500.0", InputRounding::Reject, parsed(25000000, false)),
+        ("USD", " 1.5 ", InputRounding::Reject, parsed(15000, false)),
+        ("USD", ".25", InputRounding::Reject, parsed(2500, false)),
+        ("USD", "-3", InputRounding::Reject, parsed(-30000, false)),
+        ("USD", "1.000050", InputRounding::Reject, Err(ExecutionError::InvalidAmount)),
+        ("USD", "1.000050", InputRounding::Truncate, parsed(10000, true)),
+        ("USD", "1.00005", InputRounding::HalfEven, parsed(10000, true)),
+        ("USD", "1.00015", InputRounding::HalfEven, parsed(10002, true)),
+        ("USD", "1.000051", InputRounding::HalfEven, parsed(10001, true)),
+        ("USD", "-1.00007", InputRounding::HalfEven, parsed(-10001, true)),
+        ("USD", "1.00000", InputRounding::Reject, parsed(10000, false)),
+        ("JPY", "1500", InputRounding::Reject, parsed(1500, false)),
+        ("JPY", "1500.5", InputRounding::HalfEven, parsed(1500, true)),
+        ("JPY", "1501.5", InputRounding::HalfEven, parsed(1502, true)),
+        ("BTC", "0.00000001", InputRounding::Reject, parsed(1, false)),
+        ("USD", "", InputRounding::Truncate, Err(ExecutionError::InvalidAmount)),
+        ("USD", "1e5", InputRounding::Truncate, Err(ExecutionError::InvalidAmount)),
+        ("USD", "1.2.3", InputRounding::Truncate, Err(ExecutionError::InvalidAmount)),
+        ("USD", "9999999999999999999999999999999999999999", InputRounding::Truncate, Err(ExecutionError::InvalidAmount)),
+    ).into_iter() {
+        assert_eq!(
+            scales.parse_amount(asset(code), text, rounding),
+            exp_parsed,
+            "{} {:?}",
+            code,
+            text,
+        );
+    }
+}
+
+#[test]
+fn amount_formatting() {
+    let scales = scales();
+
+    for (code, amount, exp_text) in vec!(
+        ("USD", 15000, "1.5"),
+        ("USD", 0, "0"),
+        ("USD", -20000, "-2"),
+        ("USD", 1, "0.0001"),
+        ("JPY", 1500, "1500"),
+        ("BTC", 123456789, "1.23456789"),
+        ("BTC", -10, "-0.0000001"),
+    ).into_iter() {
+        assert_eq!(scales.format_amount(asset(code), amount), exp_text);
+    }
+}
+
+#[test]
+fn fx_conversion_between_scales() {
+    let mut fx_table = FxTable::new(Rounding::HalfEven);
+    fx_table.scales = scales();
+
+    // one dollar buys 150.5 yen
+    assert!(fx_table.add_rate(asset("USD"), asset("JPY"), 15_050_000_000, 0).is_ok());
+
+    assert_eq!(fx_table.convert(20000, asset("USD"), asset("JPY"), 1), Some(301));
+    assert_eq!(fx_table.convert(301, asset("JPY"), asset("USD"), 1), Some(20000));
+    assert_eq!(fx_table.convert(1, asset("JPY"), asset("USD"), 1), Some(66));
+}
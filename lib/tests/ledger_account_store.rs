@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::policy::DefaultPolicy;
+use simledger::store::DiskAccountStore;
+use simledger::traits::execution::TransactionExecution;
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::traits::store::AccountStore;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+static CLIENT_ID_C: RawClientId = 33;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn store_dir(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("simledger-{}-{}", name, std::process::id()))
+}
+
+fn disk_ledger(
+    name: &str,
+    capacity: usize,
+) -> Ledger<DefaultPolicy, DiskAccountStore<DefaultPolicy>> {
+    let store =
+        DiskAccountStore::new(
+            store_dir(name),
+            capacity,
+            AccountConfig::default(),
+            DefaultPolicy,
+        ).unwrap();
+
+    Ledger::with_store(
+        AccountConfig::default(),
+        DefaultPolicy,
+        store,
+    )
+}
+
+#[test]
+fn ledger_disk_store_matches_memory_store() {
+    let mut ledger = Ledger::new();
+    let mut disk_ledger = disk_ledger("matches", 1);
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+        fake_tx(3, CLIENT_ID_C, TransactionTag::Deposit(1000)),
+        fake_tx(4, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_C, 2500)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Dispute(Some(2000))),
+        fake_tx(5, CLIENT_ID_C, TransactionTag::Authorize(500)),
+        // will fail because of insufficient balance
+        fake_tx(6, CLIENT_ID_B, TransactionTag::Withdrawal(4000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Chargeback(None)),
+        fake_tx(5, CLIENT_ID_C, TransactionTag::Capture),
+    ).iter() {
+        assert_eq!(
+            disk_ledger.execute_transaction(tx),
+            ledger.execute_transaction(tx),
+        );
+    }
+
+    // only the account used last is left in memory
+    assert_eq!(disk_ledger.store().in_memory(), 1);
+    assert_eq!(disk_ledger.store().spilled(), 2);
+
+    assert_eq!(disk_ledger.store().client_ids(), vec!(CLIENT_ID_A, CLIENT_ID_B, CLIENT_ID_C));
+    assert_eq!(disk_ledger.amount_total(), ledger.amount_total());
+
+    for (client_id, account) in ledger.accounts().iter() {
+        let disk_account = disk_ledger.account(*client_id).unwrap().unwrap();
+
+        assert_eq!(disk_account.balance(), account.balance());
+        assert_eq!(disk_account.history(), account.history());
+        assert_eq!(disk_account.dispute_cases(), account.dispute_cases());
+        assert_eq!(disk_account.book, account.book);
+        assert_eq!(disk_account.book_chargeback, account.book_chargeback);
+    }
+}
+
+#[test]
+fn ledger_disk_store_rolls_back_spilled_accounts() {
+    let mut ledger = disk_ledger("rollback", 2);
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    // accounts are written out and read back again within the batch
+    assert_eq!(
+        ledger.execute_batch(&[
+            fake_tx(3, CLIENT_ID_C, TransactionTag::Deposit(1000)),
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 10000)),
+            fake_tx(5, CLIENT_ID_C, TransactionTag::Withdrawal(2000)),
+        ]),
+        Err(ExecutionError::InsufficientBalance),
+    );
+
+    assert_eq!(ledger.account(CLIENT_ID_C).map(|account| account.is_none()), Ok(true));
+    assert!(ledger.store().writes() > 0);
+
+    for (client_id, exp_available, exp_history_len) in vec!(
+        (CLIENT_ID_A, 15000, 1),
+        (CLIENT_ID_B, 5000, 1),
+    ).into_iter() {
+        let account = ledger.account(client_id).unwrap().unwrap();
+
+        assert_eq!(account.amount_available(), exp_available);
+        assert_eq!(account.history().len(), exp_history_len);
+    }
+}
+
+#[test]
+fn ledger_disk_store_fails_on_unreadable_accounts() {
+    let mut ledger = disk_ledger("unreadable", 1);
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    // the spilled account goes missing behind the store's back
+    std::fs::remove_file(
+        store_dir("unreadable").join(format!("{}.account", CLIENT_ID_A)),
+    ).unwrap();
+
+    assert_eq!(
+        ledger.account(CLIENT_ID_A).map(|account| account.is_some()),
+        Err(ExecutionError::StorageFailed),
+    );
+    assert_eq!(ledger.amount_total(), Err(ExecutionError::StorageFailed));
+    assert_eq!(
+        ledger.execute_transaction(
+            &fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(1000)),
+        ),
+        Err(ExecutionError::StorageFailed),
+    );
+
+    // accounts still in memory are not affected
+    assert_eq!(
+        ledger.execute_transaction(
+            &fake_tx(4, CLIENT_ID_B, TransactionTag::Withdrawal(1000)),
+        ),
+        Ok(ExecutionResult::NewAvailableBalance(4000)),
+    );
+}
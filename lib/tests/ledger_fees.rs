@@ -0,0 +1,291 @@
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::fee::{Fee, FeeRule, FeeSchedule};
+use simledger::fx::{FxTable, Rounding};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+#[test]
+fn fee_rule_amounts() {
+    let tiered =
+        FeeRule::Tiered(
+            vec!(
+                (0, Fee::flat(100)),
+                (10000, Fee { flat: 50, basis_points: 100 }),
+                (100000, Fee::percentage(50)),
+            ),
+        );
+
+    for (rule, amount, exp_fee) in vec!(
+        (FeeRule::Fixed(Fee::flat(250)), 15000, 250),
+        (FeeRule::Fixed(Fee::percentage(150)), 20000, 300),
+        (tiered.clone(), 5000, 100),
+        (tiered.clone(), 10000, 150),
+        (tiered.clone(), 200000, 1000),
+        (FeeRule::Tiered(vec!((10000, Fee::flat(100)))), 5000, 0),
+    ).iter() {
+        assert_eq!(rule.amount_for(*amount), *exp_fee);
+    }
+}
+
+#[test]
+fn fee_percentages_are_exact() {
+    for (text, exp_basis_points) in vec!(
+        ("1", Ok(100)),
+        ("0.29", Ok(29)),
+        ("1.5", Ok(150)),
+        ("0.01", Ok(1)),
+        // fractions of a basis point can't be charged
+        ("0.125", Err(ExecutionError::InvalidAmount)),
+        ("1e2", Err(ExecutionError::InvalidAmount)),
+    ).into_iter() {
+        assert_eq!(Fee::parse_basis_points(text), exp_basis_points);
+    }
+}
+
+type TestCasesFees = Vec<(Transaction, Result<ExecutionResult, ExecutionError>, Amount)>;
+
+fn run_test_fees(fee_schedule: FeeSchedule, cases: TestCasesFees) {
+    let mut ledger = Ledger::new();
+    ledger.set_fee_schedule(fee_schedule);
+
+    for (tx, exp_result, exp_available) in cases.iter() {
+        assert_eq!(
+            &ledger.execute_transaction(tx),
+            exp_result,
+        );
+
+        assert_eq!(
+            ledger.accounts()
+                .get(&CLIENT_ID_A)
+                .map(|account| account.amount_available()),
+            Some(*exp_available),
+        );
+    }
+}
+
+#[test]
+fn ledger_charges_withdrawal_fees() {
+    run_test_fees(
+        FeeSchedule {
+            withdrawal: Some(FeeRule::Fixed(Fee { flat: 100, basis_points: 100 })),
+            ..FeeSchedule::default()
+        },
+        vec!(
+            (
+                // deposits are free
+                fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                15000,
+            ),
+            (
+                fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(10000)),
+                Ok(ExecutionResult::NewAvailableBalance(5000)),
+                4800,
+            ),
+            (
+                // the fee is not covered, the withdrawal is rolled back
+                fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(4800)),
+                Err(ExecutionError::InsufficientBalance),
+                4800,
+            ),
+            (
+                fake_tx(4, CLIENT_ID_A, TransactionTag::Withdrawal(4600)),
+                Ok(ExecutionResult::NewAvailableBalance(200)),
+                54,
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_charges_chargeback_fees() {
+    run_test_fees(
+        FeeSchedule {
+            chargeback: Some(FeeRule::Fixed(Fee::flat(1500))),
+            ..FeeSchedule::default()
+        },
+        vec!(
+            (
+                fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                Ok(ExecutionResult::NewAvailableBalance(15000)),
+                15000,
+            ),
+            (
+                fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                Ok(ExecutionResult::Ok),
+                0,
+            ),
+            (
+                // chargeback fees may overdraw the account
+                fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+                Ok(ExecutionResult::Ok),
+                -1500,
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_books_fees_apart() {
+    let mut ledger = Ledger::new();
+
+    ledger.set_fee_schedule(
+        FeeSchedule {
+            transfer: Some(FeeRule::Fixed(Fee::percentage(200))),
+            ..FeeSchedule::default()
+        },
+    );
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 10000)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 2500)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    // fees can't be submitted from the outside
+    assert_eq!(
+        ledger.execute_transaction(&fake_tx(4, CLIENT_ID_B, TransactionTag::Fee(100))),
+        Err(ExecutionError::InvalidTransactionType),
+    );
+
+    let account = &ledger.accounts()[&CLIENT_ID_A];
+
+    assert_eq!(
+        account.book_fees
+            .iter()
+            .map(|entry| (entry.tx_id, entry.amount))
+            .collect::<Vec<_>>(),
+        vec!((2, 200), (3, 50)),
+    );
+
+    // the sender pays, the recipient receives the full amount
+    assert_eq!(account.amount_available(), 2250);
+    assert_eq!(ledger.accounts()[&CLIENT_ID_B].amount_available(), 12500);
+    assert_eq!(ledger.total_fees(), Ok(250));
+
+    // fees show up in the statement right after their transaction
+    assert_eq!(
+        account.history()
+            .iter()
+            .map(|entry| (entry.seq, entry.tx.tag))
+            .collect::<Vec<_>>(),
+        vec!(
+            (1, TransactionTag::Deposit(15000)),
+            (2, TransactionTag::Transfer(CLIENT_ID_B, 10000)),
+            (2, TransactionTag::Fee(200)),
+            (3, TransactionTag::Transfer(CLIENT_ID_B, 2500)),
+            (3, TransactionTag::Fee(50)),
+        ),
+    );
+}
+
+#[test]
+fn ledger_fees_keep_duplicate_attempts() {
+    let mut ledger = Ledger::new();
+
+    ledger.set_fee_schedule(
+        FeeSchedule {
+            deposit: Some(FeeRule::Fixed(Fee::flat(10))),
+            ..FeeSchedule::default()
+        },
+    );
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+        ),
+        (
+            // rolled back along with its fee, the attempt still counts
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+            Err(ExecutionError::TransactionExists),
+        ),
+    ).into_iter() {
+        assert_eq!(ledger.execute_transaction(&tx), exp_result);
+    }
+
+    assert_eq!(ledger.accounts()[&CLIENT_ID_A].duplicate_attempts(), 2);
+}
+
+#[test]
+fn ledger_converts_fees_on_other_assets() {
+    let eur = Asset::new("EUR").unwrap();
+
+    let mut fx_table = FxTable::new(Rounding::HalfEven);
+    assert!(fx_table.add_rate(eur, Asset::default(), 200_000_000, 0).is_ok());
+
+    for (fee, fx_table, exp_result, exp_available) in vec!(
+        (
+            // the flat 1.00 USD costs 0.50 EUR
+            Fee { flat: 100, basis_points: 100 },
+            Some(fx_table.clone()),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+            14800,
+        ),
+        (
+            Fee { flat: 100, basis_points: 0 },
+            None,
+            Err(ExecutionError::RateUnavailable),
+            0,
+        ),
+        (
+            // percentages need no rate
+            Fee::percentage(100),
+            None,
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+            14850,
+        ),
+    ).into_iter() {
+        let mut ledger = Ledger::new();
+
+        ledger.set_fee_schedule(
+            FeeSchedule {
+                deposit: Some(FeeRule::Fixed(fee)),
+                ..FeeSchedule::default()
+            },
+        );
+
+        if let Some(fx_table) = fx_table {
+            ledger.set_fx_table(fx_table);
+        }
+
+        let mut tx = fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000));
+        tx.asset = Some(eur);
+
+        assert_eq!(ledger.execute_transaction(&tx), exp_result);
+
+        assert_eq!(
+            ledger.accounts()
+                .get(&CLIENT_ID_A)
+                .map_or(0, |account| account.amount_available_in(eur)),
+            exp_available,
+        );
+    }
+}
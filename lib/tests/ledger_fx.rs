@@ -0,0 +1,187 @@
+use simledger::asset::Asset;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::fx::{FxTable, Rounding};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+
+fn usd() -> Asset {
+    Asset::default()
+}
+
+fn eur() -> Asset {
+    Asset::new("EUR").unwrap()
+}
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+    asset: Option<Asset>,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset,
+    }
+}
+
+#[test]
+fn fx_rates_by_sequence() {
+    let mut fx_table = FxTable::default();
+
+    assert!(fx_table.add_rate(eur(), usd(), 100_000_000, 3).is_ok());
+    assert!(fx_table.add_rate(eur(), usd(), 200_000_000, 5).is_ok());
+    assert!(fx_table.add_rate(eur(), usd(), 150_000_000, 5).is_ok());
+
+    assert_eq!(fx_table.add_rate(eur(), usd(), 0, 7), Err(ExecutionError::InvalidAmount));
+    assert_eq!(fx_table.add_rate(eur(), eur(), 100_000_000, 7), Err(ExecutionError::InvalidAmount));
+
+    assert_eq!(fx_table.rate(eur(), usd(), 2), None);
+    assert_eq!(fx_table.rate(eur(), usd(), 4), Some(100_000_000));
+    assert_eq!(fx_table.rate(eur(), usd(), 9), Some(150_000_000));
+    assert_eq!(fx_table.rate(usd(), eur(), 9), None);
+}
+
+#[test]
+fn fx_rates_are_exact() {
+    for (text, exp_rate) in vec!(
+        ("1.08", Ok(108_000_000)),
+        ("0.00000001", Ok(1)),
+        ("1.1", Ok(110_000_000)),
+        (" 92233720368.54775807 ", Ok(i64::MAX)),
+        ("92233720368.54775808", Err(ExecutionError::InvalidAmount)),
+        ("1.000000001", Err(ExecutionError::InvalidAmount)),
+        ("1e3", Err(ExecutionError::InvalidAmount)),
+    ).into_iter() {
+        assert_eq!(FxTable::parse_rate(text), exp_rate);
+    }
+}
+
+#[test]
+fn fx_conversion_rounding() {
+    for (rounding, rate, from, to, amount, exp_converted) in vec!(
+        (Rounding::Down, 108_000_000, eur(), usd(), 10001, Some(10801)),
+        (Rounding::Up, 108_000_000, eur(), usd(), 10001, Some(10802)),
+        (Rounding::HalfEven, 108_000_000, eur(), usd(), 10001, Some(10801)),
+        (Rounding::HalfEven, 150_000_000, eur(), usd(), 3, Some(4)),
+        (Rounding::HalfEven, 150_000_000, eur(), usd(), 5, Some(8)),
+        (Rounding::Up, 150_000_000, eur(), usd(), 4, Some(6)),
+        // inverse of the opposite pair
+        (Rounding::HalfEven, 125_000_000, usd(), eur(), 10000, Some(8000)),
+        (Rounding::Down, 300_000_000, usd(), eur(), 10000, Some(3333)),
+        (Rounding::Up, 300_000_000, usd(), eur(), 10000, Some(3334)),
+        (Rounding::HalfEven, 150_000_000, eur(), Asset::new("BTC").unwrap(), 10000, None),
+    ).into_iter() {
+        let mut fx_table = FxTable::new(rounding);
+
+        assert!(fx_table.add_rate(eur(), usd(), rate, 0).is_ok());
+
+        assert_eq!(
+            fx_table.convert(amount, from, to, 1),
+            exp_converted,
+        );
+    }
+}
+
+#[test]
+fn ledger_funds_withdrawal_from_base_asset() {
+    let mut ledger = Ledger::new();
+
+    let mut fx_table = FxTable::new(Rounding::HalfEven);
+    assert!(fx_table.add_rate(eur(), usd(), 125_000_000, 0).is_ok());
+    assert!(fx_table.add_rate(eur(), usd(), 200_000_000, 6).is_ok());
+
+    ledger.set_fx_table(fx_table);
+    ledger.enable_general_ledger();
+
+    for (tx, exp_result, exp_available) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(20000), None),
+            Ok(ExecutionResult::NewAvailableBalance(20000)),
+            (20000, 0),
+        ),
+        (
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(1000), Some(eur())),
+            Ok(ExecutionResult::NewAvailableBalance(1000)),
+            (20000, 1000),
+        ),
+        (
+            // 4000 EUR are bought for 5000 USD
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(5000), Some(eur())),
+            Ok(ExecutionResult::NewAvailableBalance(0)),
+            (15000, 0),
+        ),
+        (
+            // would cost 25000 USD, nothing is converted
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Withdrawal(20000), Some(eur())),
+            Err(ExecutionError::InsufficientBalance),
+            (15000, 0),
+        ),
+        (
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Deposit(2000), Some(eur())),
+            Ok(ExecutionResult::NewAvailableBalance(2000)),
+            (15000, 2000),
+        ),
+        (
+            // at the rate effective from the 6th transaction on
+            fake_tx(6, CLIENT_ID_A, TransactionTag::Withdrawal(3000), Some(eur())),
+            Ok(ExecutionResult::NewAvailableBalance(0)),
+            (13000, 0),
+        ),
+    ).into_iter() {
+        assert_eq!(ledger.execute_transaction(&tx), exp_result);
+
+        let account = &ledger.accounts()[&CLIENT_ID_A];
+
+        assert_eq!(
+            (account.amount_available(), account.amount_available_in(eur())),
+            exp_available,
+        );
+    }
+
+    // both legs of every conversion show up in the history
+    let account = &ledger.accounts()[&CLIENT_ID_A];
+
+    let history: Vec<(RawTxId, TransactionTag, Asset)> =
+        account.history()
+            .iter()
+            .map(|entry| (entry.tx.id, entry.tx.tag, entry.asset))
+            .collect();
+
+    assert_eq!(
+        history,
+        vec!(
+            (1, TransactionTag::Deposit(20000), usd()),
+            (2, TransactionTag::Deposit(1000), eur()),
+            (3, TransactionTag::ConversionOut(eur(), 5000), usd()),
+            (3, TransactionTag::ConversionIn(usd(), 4000), eur()),
+            (3, TransactionTag::Withdrawal(5000), eur()),
+            (5, TransactionTag::Deposit(2000), eur()),
+            (6, TransactionTag::ConversionOut(eur(), 2000), usd()),
+            (6, TransactionTag::ConversionIn(usd(), 1000), eur()),
+            (6, TransactionTag::Withdrawal(3000), eur()),
+        ),
+    );
+
+    assert!(ledger.general_ledger().unwrap().is_balanced());
+}
+
+#[test]
+fn ledger_rejects_submitted_conversions() {
+    let mut ledger = Ledger::new();
+
+    assert!(ledger.execute_transaction(&fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(20000), None)).is_ok());
+
+    assert_eq!(
+        ledger.execute_transaction(
+            &fake_tx(2, CLIENT_ID_A, TransactionTag::ConversionIn(usd(), 5000), Some(eur())),
+        ),
+        Err(ExecutionError::InvalidTransactionType),
+    );
+}
@@ -0,0 +1,177 @@
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::general_ledger::{GlAccount, TrialBalanceRow};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::TransactionExecution;
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn row(account: GlAccount, debit: Amount, credit: Amount) -> TrialBalanceRow {
+    TrialBalanceRow {
+        asset: Asset::default(),
+        account,
+        debit,
+        credit,
+    }
+}
+
+type TestCasesTrialBalance = Vec<(Vec<Transaction>, Vec<TrialBalanceRow>)>;
+
+fn run_test_trial_balance(cases: TestCasesTrialBalance) {
+    for (txs, exp_rows) in cases.iter() {
+        let mut ledger = Ledger::new();
+        ledger.enable_general_ledger();
+
+        for tx in txs.iter() {
+            let _ = ledger.execute_transaction(tx);
+
+            assert!(ledger.general_ledger().unwrap().is_balanced());
+        }
+
+        let general_ledger = ledger.general_ledger().unwrap();
+
+        assert_eq!(
+            &general_ledger.trial_balance(),
+            exp_rows,
+        );
+
+        // client liabilities reconcile with the client balances
+        for (client_id, account) in ledger.accounts().iter() {
+            let liabilities =
+                general_ledger.postings()
+                    .iter()
+                    .filter(|posting| posting.account == GlAccount::ClientLiabilities(*client_id))
+                    .map(|posting| -posting.amount)
+                    .sum::<Amount>();
+
+            assert_eq!(
+                liabilities,
+                account.amount_available() + account.amount_lost(),
+            );
+        }
+    }
+}
+
+#[test]
+fn general_ledger_balance_flows() {
+    run_test_trial_balance(
+        vec!(
+            (
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+                    // rejected transactions are not posted
+                    fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(50000)),
+                ),
+                vec!(
+                    row(GlAccount::SettlementCash, 10000, 0),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 10000),
+                ),
+            ),
+            (
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Authorize(5000)),
+                    fake_tx(3, CLIENT_ID_A, TransactionTag::Authorize(2000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Capture),
+                ),
+                vec!(
+                    row(GlAccount::SettlementCash, 10000, 0),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 8000),
+                    row(GlAccount::AuthorizationReserve, 0, 2000),
+                ),
+            ),
+            (
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                ),
+                vec!(
+                    row(GlAccount::SettlementCash, 15000, 0),
+                    row(GlAccount::TransferClearing, 0, 0),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 10000),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_B), 0, 5000),
+                ),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn general_ledger_dispute_process() {
+    run_test_trial_balance(
+        vec!(
+            (
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                ),
+                vec!(
+                    row(GlAccount::SettlementCash, 20000, 0),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 5000),
+                    row(GlAccount::DisputeReserve, 0, 15000),
+                ),
+            ),
+            (
+                // the deficit left by the chargeback is written off
+                vec!(
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+                    fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(10000)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                    fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+                ),
+                vec!(
+                    row(GlAccount::SettlementCash, 0, 10000),
+                    row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 0),
+                    row(GlAccount::DisputeReserve, 0, 0),
+                    row(GlAccount::ChargebackLosses, 10000, 0),
+                ),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn general_ledger_rollback_discards_postings() {
+    let mut ledger = Ledger::new();
+    ledger.enable_general_ledger();
+
+    ledger.execute_transaction(
+        &fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+    ).unwrap();
+
+    let savepoint = ledger.savepoint();
+
+    ledger.execute_transaction(
+        &fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+    ).unwrap();
+
+    ledger.rollback_to(savepoint).unwrap();
+
+    assert_eq!(
+        ledger.general_ledger().unwrap().trial_balance(),
+        vec!(
+            row(GlAccount::SettlementCash, 15000, 0),
+            row(GlAccount::ClientLiabilities(CLIENT_ID_A), 0, 15000),
+        ),
+    );
+}
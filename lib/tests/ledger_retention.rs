@@ -0,0 +1,375 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::config::{AccountConfig, DisputeWindow};
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::fee::{Fee, FeeRule, FeeSchedule};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::policy::DefaultPolicy;
+use simledger::retention::{Retention, RetentionStats, MAX_ID_RANGES};
+use simledger::rules::{VelocityLimit, WithdrawalRules};
+use simledger::store::DiskAccountStore;
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn timed_tx(
+    id: RawTxId,
+    timestamp: u64,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        timestamp: Some(timestamp),
+        ..fake_tx(id, CLIENT_ID_A, tag)
+    }
+}
+
+#[test]
+fn account_compacts_by_count() {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                retention: Retention::Count(2),
+                ..AccountConfig::default()
+            },
+        );
+
+    for id in 1..=6 {
+        account.execute_transaction(&fake_tx(id, CLIENT_ID_A, TransactionTag::Deposit(1000))).unwrap();
+        account.compact();
+    }
+
+    assert_eq!(account.amount_available(), 6000);
+    assert_eq!(account.history().len(), 2);
+    assert_eq!(account.book.keys().copied().collect::<Vec<RawTxId>>(), vec!(5, 6));
+
+    assert_eq!(
+        (account.compacted().book_entries, account.compacted().history_entries),
+        (4, 4),
+    );
+    assert_eq!(account.compacted().id_ranges(), 1);
+
+    // balances before the retained history are known as of the last compacted entry
+    for (seq, exp_available) in vec!(
+        (2, Err(ExecutionError::TransactionCompacted)),
+        (4, Ok(4000)),
+        (5, Ok(5000)),
+    ).into_iter() {
+        assert_eq!(account.balance_at(seq).map(|balance| balance.available), exp_available);
+    }
+
+    for (tx, exp_result) in vec!(
+        (
+            // compacted ids may not be booked again
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Dispute(None)),
+            Err(ExecutionError::TransactionCompacted),
+        ),
+        (
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Dispute(None)),
+            Ok(ExecutionResult::Ok),
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+    }
+}
+
+#[test]
+fn account_compaction_is_bounded() {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                retention: Retention::Count(2),
+                ..AccountConfig::default()
+            },
+        );
+
+    // the ids of other clients lie in between
+    for id in 1..=500 {
+        account.execute_transaction(&fake_tx(id * 10, CLIENT_ID_A, TransactionTag::Deposit(100))).unwrap();
+        account.compact();
+    }
+
+    assert_eq!(account.compacted().id_ranges(), MAX_ID_RANGES);
+
+    for (tx, exp_result) in vec!(
+        (
+            // folded into the highest compacted id
+            fake_tx(10, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(4990, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Err(ExecutionError::TransactionExists),
+        ),
+        (
+            fake_tx(5001, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Ok(ExecutionResult::NewAvailableBalance(50100)),
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+    }
+}
+
+#[test]
+fn account_compacts_closed_dispute_cases() {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                retention: Retention::Count(2),
+                ..AccountConfig::default()
+            },
+        );
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Resolve(None)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+        fake_tx(4, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+        fake_tx(5, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+    ).iter() {
+        account.execute_transaction(tx).unwrap();
+        account.compact();
+    }
+
+    // the open case is kept, the resolved one is gone
+    assert_eq!(account.compacted().dispute_cases, 1);
+    assert_eq!(
+        account.dispute_cases()
+            .iter()
+            .map(|case| (case.case_id, case.tx_id))
+            .collect::<Vec<_>>(),
+        vec!((2, 2)),
+    );
+
+    // case ids carry on after the compacted ones
+    account.execute_transaction(&fake_tx(5, CLIENT_ID_A, TransactionTag::Dispute(None))).unwrap();
+
+    assert_eq!(account.dispute_case(3).map(|case| case.tx_id), Some(5));
+}
+
+#[test]
+fn account_compaction_keeps_rule_windows() {
+    let mut account =
+        Account::with_config(
+            CLIENT_ID_A,
+            AccountConfig {
+                retention: Retention::Count(2),
+                withdrawal_rules: WithdrawalRules {
+                    velocity_per_transactions: Some(VelocityLimit { max_withdrawals: 2, per: 5 }),
+                    ..WithdrawalRules::default()
+                },
+                ..AccountConfig::default()
+            },
+        );
+
+    for (tx, exp_result) in vec!(
+        (
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(10000)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+        (
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(100)),
+            Ok(ExecutionResult::NewAvailableBalance(9900)),
+        ),
+        (
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+        (
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Withdrawal(100)),
+            Ok(ExecutionResult::NewAvailableBalance(9900)),
+        ),
+        (
+            // the first withdrawal is out of retention but still within the rule window
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Withdrawal(100)),
+            Err(ExecutionError::VelocityLimitExceeded),
+        ),
+        (
+            fake_tx(6, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+        (
+            fake_tx(7, CLIENT_ID_A, TransactionTag::Deposit(100)),
+            Ok(ExecutionResult::NewAvailableBalance(10100)),
+        ),
+        (
+            // only the withdrawal of tx 4 is left within the window
+            fake_tx(8, CLIENT_ID_A, TransactionTag::Withdrawal(100)),
+            Ok(ExecutionResult::NewAvailableBalance(10000)),
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+
+        account.compact();
+    }
+
+    // the last four entries are read by the rule
+    assert_eq!(
+        account.history()
+            .iter()
+            .map(|entry| entry.tx.id)
+            .collect::<Vec<RawTxId>>(),
+        vec!(4, 6, 7, 8),
+    );
+
+    // compacted deposits still count towards the dispute ratio of the risk score
+    assert_eq!(account.compacted().deposits, 2);
+}
+
+#[test]
+fn ledger_compacts_by_dispute_window() {
+    let mut ledger =
+        Ledger::with_config(
+            AccountConfig {
+                dispute_window: DisputeWindow::Seconds(100),
+                retention: Retention::DisputeWindow,
+                ..AccountConfig::default()
+            },
+        );
+
+    ledger.set_fee_schedule(
+        FeeSchedule {
+            deposit: Some(FeeRule::Fixed(Fee::flat(10))),
+            ..FeeSchedule::default()
+        },
+    );
+
+    for tx in vec!(
+        timed_tx(1, 0, TransactionTag::Deposit(1000)),
+        timed_tx(2, 50, TransactionTag::Deposit(1000)),
+        timed_tx(3, 120, TransactionTag::Deposit(1000)),
+        timed_tx(4, 200, TransactionTag::Deposit(1000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    // the deposits and fees of the first two transactions are out of the window
+    assert_eq!(
+        ledger.retention_stats(),
+        Ok(RetentionStats {
+            book_entries: 4,
+            history_entries: 4,
+
+            compacted_book_entries: 2,
+            compacted_history_entries: 4,
+        }),
+    );
+
+    // fees of compacted entries still count
+    assert_eq!(ledger.total_fees(), Ok(40));
+    assert_eq!(ledger.accounts()[&CLIENT_ID_A].amount_available(), 3960);
+
+    for (tx, exp_result) in vec!(
+        (
+            timed_tx(1, 210, TransactionTag::Dispute(None)),
+            Err(ExecutionError::TransactionCompacted),
+        ),
+        (
+            timed_tx(3, 210, TransactionTag::Dispute(None)),
+            Ok(ExecutionResult::Ok),
+        ),
+        (
+            timed_tx(2, 220, TransactionTag::Deposit(1000)),
+            Err(ExecutionError::TransactionExists),
+        ),
+    ).into_iter() {
+        assert_eq!(ledger.execute_transaction(&tx), exp_result);
+    }
+}
+
+#[test]
+fn ledger_balance_at_before_retention() {
+    let mut ledger =
+        Ledger::with_config(
+            AccountConfig {
+                retention: Retention::Count(2),
+                ..AccountConfig::default()
+            },
+        );
+
+    for id in 1..=10 {
+        ledger.execute_transaction(&fake_tx(id, CLIENT_ID_A, TransactionTag::Deposit(100))).unwrap();
+    }
+
+    // balances that were compacted away are never reported as empty
+    for seq in 1..=7 {
+        assert_eq!(
+            ledger.balance_at(CLIENT_ID_A, seq),
+            Err(ExecutionError::TransactionCompacted),
+        );
+    }
+
+    // the last compacted balance is still known
+    for seq in 8..=10 {
+        assert_eq!(
+            ledger.balance_at(CLIENT_ID_A, seq)
+                .map(|balance| balance.map(|balance| balance.available)),
+            Ok(Some(seq as Amount * 100)),
+        );
+    }
+}
+
+#[test]
+fn ledger_compaction_survives_disk_store() {
+    let account_config = AccountConfig {
+        retention: Retention::Count(3),
+        ..AccountConfig::default()
+    };
+
+    let mut ledger = Ledger::with_config(account_config);
+
+    let store =
+        DiskAccountStore::new(
+            std::env::temp_dir().join(format!("simledger-retention-{}", std::process::id())),
+            1,
+            account_config,
+            DefaultPolicy,
+        ).unwrap();
+
+    let mut disk_ledger = Ledger::with_store(account_config, DefaultPolicy, store);
+
+    for id in 1..=20 {
+        let client_id = if id % 2 == 0 { CLIENT_ID_A } else { CLIENT_ID_B };
+        let tx = fake_tx(id, client_id, TransactionTag::Deposit(1000));
+
+        assert_eq!(
+            disk_ledger.execute_transaction(&tx),
+            ledger.execute_transaction(&tx),
+        );
+    }
+
+    assert_eq!(disk_ledger.retention_stats(), ledger.retention_stats());
+
+    for (client_id, account) in ledger.accounts().iter() {
+        let disk_account = disk_ledger.account(*client_id).unwrap().unwrap();
+
+        assert_eq!(disk_account.compacted(), account.compacted());
+        assert_eq!(disk_account.history(), account.history());
+    }
+}
@@ -0,0 +1,209 @@
+use simledger::amount::Amount;
+use simledger::config::AccountConfig;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::retention::Retention;
+use simledger::traits::execution::TransactionExecution;
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn assert_balances(
+    ledger: &Ledger,
+    client_id: RawClientId,
+    (exp_total, exp_avail, exp_held, exp_locked): (Amount, Amount, Amount, bool),
+) {
+    let account = ledger.accounts().get(&client_id).unwrap();
+
+    assert_eq!(account.amount_total(), exp_total);
+    assert_eq!(account.amount_available(), exp_avail);
+    assert_eq!(account.amount_held(), exp_held);
+    assert_eq!(account.locked(), exp_locked);
+}
+
+#[test]
+fn ledger_rollback_restores_account_state() {
+    let mut ledger = Ledger::new();
+
+    ledger.execute_transaction(&fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000))).unwrap();
+    ledger.execute_transaction(&fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000))).unwrap();
+
+    let savepoint = ledger.savepoint();
+
+    // suspicious sequence: dispute, drain and chargeback
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+        fake_tx(4, CLIENT_ID_B, TransactionTag::Deposit(1000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    assert_balances(&ledger, CLIENT_ID_A, (0, 0, 0, true));
+
+    assert_eq!(
+        ledger.rollback_to(savepoint),
+        Ok(ExecutionResult::Ok),
+    );
+
+    assert_balances(&ledger, CLIENT_ID_A, (20000, 20000, 0, false));
+    assert!(ledger.accounts().get(&CLIENT_ID_B).is_none());
+
+    let account = ledger.accounts().get(&CLIENT_ID_A).unwrap();
+
+    assert_eq!(account.book.len(), 2);
+    assert!(account.book_disputed.is_empty());
+    assert!(account.book_chargeback.is_empty());
+
+    // ids used inside of the rolled back range are free again
+    assert_eq!(
+        ledger.execute_transaction(&fake_tx(3, CLIENT_ID_A, TransactionTag::Withdrawal(1000))),
+        Ok(ExecutionResult::NewAvailableBalance(19000)),
+    );
+}
+
+#[test]
+fn ledger_nested_savepoints() {
+    let mut ledger = Ledger::new();
+
+    let outer = ledger.savepoint();
+
+    ledger.execute_transaction(&fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000))).unwrap();
+
+    let inner = ledger.savepoint();
+
+    ledger.execute_transaction(&fake_tx(1, CLIENT_ID_A, TransactionTag::Dispute(None))).unwrap();
+
+    assert_eq!(ledger.rollback_to(inner), Ok(ExecutionResult::Ok));
+    assert_balances(&ledger, CLIENT_ID_A, (15000, 15000, 0, false));
+
+    assert_eq!(ledger.rollback_to(outer), Ok(ExecutionResult::Ok));
+    assert!(ledger.accounts().get(&CLIENT_ID_A).is_none());
+
+    // rolling back the outer savepoint discards the inner one
+    assert_eq!(
+        ledger.rollback_to(inner),
+        Err(ExecutionError::InvalidSavepoint),
+    );
+
+    assert_eq!(ledger.release_savepoint(outer), Ok(ExecutionResult::Ok));
+    assert_eq!(
+        ledger.release_savepoint(outer),
+        Err(ExecutionError::InvalidSavepoint),
+    );
+}
+
+#[test]
+fn ledger_rejects_stale_savepoints() {
+    let mut ledger = Ledger::new();
+
+    let released = ledger.savepoint();
+
+    assert_eq!(ledger.release_savepoint(released), Ok(ExecutionResult::Ok));
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(5000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    // opened at the same depth and journal length as the released one
+    let current = ledger.savepoint();
+
+    assert_eq!(
+        ledger.rollback_to(released),
+        Err(ExecutionError::InvalidSavepoint),
+    );
+    assert_eq!(
+        ledger.release_savepoint(released),
+        Err(ExecutionError::InvalidSavepoint),
+    );
+
+    assert_eq!(ledger.sequence(), 2);
+    assert_balances(&ledger, CLIENT_ID_A, (20000, 20000, 0, false));
+
+    assert_eq!(ledger.release_savepoint(current), Ok(ExecutionResult::Ok));
+}
+
+#[test]
+fn ledger_execute_batch_is_atomic() {
+    let mut ledger = Ledger::new();
+
+    assert_eq!(
+        ledger.execute_batch(&[
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Withdrawal(5000)),
+        ]),
+        Ok(ExecutionResult::Ok),
+    );
+
+    assert_eq!(
+        ledger.execute_batch(&[
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            fake_tx(4, CLIENT_ID_B, TransactionTag::Deposit(1000)),
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Withdrawal(50000)),
+        ]),
+        Err(ExecutionError::InsufficientBalance),
+    );
+
+    assert_balances(&ledger, CLIENT_ID_A, (10000, 10000, 0, false));
+    assert!(ledger.accounts().get(&CLIENT_ID_B).is_none());
+}
+
+#[test]
+fn ledger_execute_batch_compacts_once_applied() {
+    let mut ledger =
+        Ledger::with_config(
+            AccountConfig {
+                retention: Retention::Age(2),
+                ..AccountConfig::default()
+            },
+        );
+
+    assert_eq!(
+        ledger.execute_batch(&[
+            fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            fake_tx(2, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            fake_tx(3, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            fake_tx(4, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+        ]),
+        Ok(ExecutionResult::Ok),
+    );
+
+    assert_eq!(
+        ledger.execute_batch(&[
+            fake_tx(5, CLIENT_ID_A, TransactionTag::Deposit(1000)),
+            fake_tx(6, CLIENT_ID_A, TransactionTag::Withdrawal(50000)),
+        ]),
+        Err(ExecutionError::InsufficientBalance),
+    );
+
+    assert_eq!(ledger.sequence(), 4);
+    assert_balances(&ledger, CLIENT_ID_A, (4000, 4000, 0, false));
+
+    // entries older than two transactions were compacted after the first batch
+    let account = ledger.accounts().get(&CLIENT_ID_A).unwrap();
+
+    assert_eq!(account.history().len(), 2);
+    assert_eq!(account.compacted().history_entries, 2);
+    assert_eq!(account.compacted().id_ranges(), 1);
+}
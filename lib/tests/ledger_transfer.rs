@@ -0,0 +1,291 @@
+use simledger::account::Account;
+use simledger::amount::Amount;
+use simledger::asset::Asset;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{RawClientId, RawTxId};
+use simledger::ledger::Ledger;
+use simledger::traits::execution::{SequencedTransactionExecution, TransactionExecution};
+use simledger::traits::savepoint::LedgerSavepoints;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    client_id: RawClientId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+fn available(ledger: &Ledger, client_id: RawClientId) -> Amount {
+    ledger.accounts()
+        .get(&client_id)
+        .map(|account| account.amount_available())
+        .unwrap_or(0)
+}
+
+type TestCasesTransfer = Vec<(Vec<(Transaction, Result<ExecutionResult, ExecutionError>)>, (Amount, Amount))>;
+
+fn run_test_transfer(cases: TestCasesTransfer) {
+    for (txs, exp_balances) in cases.iter() {
+        let mut ledger = Ledger::new();
+
+        ledger.execute_transaction(
+            &fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        ).unwrap();
+
+        for (tx, exp_result) in txs.iter() {
+            let amount_total = ledger.amount_total();
+
+            assert_eq!(
+                &ledger.execute_transaction(tx),
+                exp_result,
+            );
+
+            // transfers never create or destroy funds
+            if let TransactionTag::Transfer(_, _)
+            | TransactionTag::TransferReversal(_) = tx.tag {
+                assert_eq!(ledger.amount_total(), amount_total);
+            }
+        }
+
+        assert_eq!(
+            (
+                available(&ledger, CLIENT_ID_A),
+                available(&ledger, CLIENT_ID_B),
+            ),
+            *exp_balances,
+        );
+    }
+}
+
+#[test]
+fn ledger_transfer_moves_funds_atomically() {
+    run_test_transfer(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                ),
+                (10000, 5000),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 20000)),
+                        Err(ExecutionError::InsufficientBalance),
+                    ),
+                    (
+                        fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_A, 5000)),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                    (
+                        // legs are only ever booked by the ledger
+                        fake_tx(4, CLIENT_ID_B, TransactionTag::TransferIn(CLIENT_ID_A, 5000)),
+                        Err(ExecutionError::InvalidTransactionType),
+                    ),
+                ),
+                (15000, 0),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(1000)),
+                        Ok(ExecutionResult::NewAvailableBalance(1000)),
+                    ),
+                    (
+                        // the recipient leg fails, the sender leg is rolled back
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Err(ExecutionError::TransactionExists),
+                    ),
+                ),
+                (15000, 1000),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_transfer_reversal() {
+    run_test_transfer(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::TransferReversal(Some(2000))),
+                        Ok(ExecutionResult::NewAvailableBalance(12000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::TransferReversal(None)),
+                        Ok(ExecutionResult::NewAvailableBalance(15000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::TransferReversal(None)),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                ),
+                (15000, 0),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        fake_tx(3, CLIENT_ID_B, TransactionTag::Withdrawal(4000)),
+                        Ok(ExecutionResult::NewAvailableBalance(1000)),
+                    ),
+                    (
+                        // the recipient already spent the funds
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::TransferReversal(None)),
+                        Err(ExecutionError::InsufficientBalance),
+                    ),
+                    (
+                        // only the sender administers a transfer
+                        fake_tx(2, CLIENT_ID_B, TransactionTag::TransferReversal(None)),
+                        Err(ExecutionError::InvalidTransactionType),
+                    ),
+                ),
+                (10000, 1000),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_transfer_dispute_process() {
+    run_test_transfer(
+        vec!(
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        // the recipient can't dispute funds sent to it
+                        fake_tx(2, CLIENT_ID_B, TransactionTag::Dispute(None)),
+                        Err(ExecutionError::InvalidTransactionType),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        // disputed transfers can't be reversed
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::TransferReversal(None)),
+                        Err(ExecutionError::InvalidTransaction),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Resolve(None)),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                ),
+                (10000, 5000),
+            ),
+            (
+                vec!(
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+                        Ok(ExecutionResult::NewAvailableBalance(10000)),
+                    ),
+                    (
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Dispute(None)),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        // charged back funds are returned to the sender,
+                        // the rest is released to the recipient
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Chargeback(Some(3000))),
+                        Ok(ExecutionResult::Ok),
+                    ),
+                    (
+                        // transfers can't be represented
+                        fake_tx(2, CLIENT_ID_A, TransactionTag::Representment),
+                        Err(ExecutionError::InvalidTransactionType),
+                    ),
+                ),
+                (13000, 2000),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn ledger_transfer_sum_of_balances_unchanged() {
+    let mut ledger = Ledger::new();
+
+    for tx in vec!(
+        fake_tx(1, CLIENT_ID_A, TransactionTag::Deposit(15000)),
+        fake_tx(2, CLIENT_ID_B, TransactionTag::Deposit(5000)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+    }
+
+    let amount_total = ledger.amount_total();
+    let savepoint = ledger.savepoint();
+
+    for tx in vec!(
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Transfer(CLIENT_ID_B, 7500)),
+        fake_tx(4, CLIENT_ID_B, TransactionTag::Transfer(CLIENT_ID_A, 10000)),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Dispute(Some(2500))),
+        fake_tx(4, CLIENT_ID_B, TransactionTag::TransferReversal(Some(1000))),
+        fake_tx(3, CLIENT_ID_A, TransactionTag::Chargeback(None)),
+    ).iter() {
+        ledger.execute_transaction(tx).unwrap();
+
+        assert_eq!(ledger.amount_total(), amount_total);
+    }
+
+    assert_eq!(available(&ledger, CLIENT_ID_A), 19000);
+    assert_eq!(available(&ledger, CLIENT_ID_B), 1000);
+
+    // both legs of every transfer are rolled back
+    ledger.rollback_to(savepoint).unwrap();
+
+    assert_eq!(available(&ledger, CLIENT_ID_A), 15000);
+    assert_eq!(available(&ledger, CLIENT_ID_B), 5000);
+}
+
+#[test]
+fn account_rejects_ledger_legs() {
+    let mut account = Account::new(CLIENT_ID_B);
+
+    account.execute_transaction(&fake_tx(1, CLIENT_ID_B, TransactionTag::Deposit(1000))).unwrap();
+
+    for tag in vec!(
+        TransactionTag::TransferIn(CLIENT_ID_A, 5000),
+        TransactionTag::Fee(500),
+        TransactionTag::ConversionOut(Asset::new("EUR").unwrap(), 500),
+        TransactionTag::ConversionIn(Asset::new("EUR").unwrap(), 500),
+    ).into_iter() {
+        assert_eq!(
+            account.execute_transaction(&fake_tx(2, CLIENT_ID_B, tag)),
+            Err(ExecutionError::InvalidTransactionType),
+        );
+
+        assert_eq!(
+            account.execute_sequenced_transaction(2, &fake_tx(2, CLIENT_ID_B, tag)),
+            Err(ExecutionError::InvalidTransactionType),
+        );
+    }
+
+    assert_eq!(account.amount_available(), 1000);
+    assert_eq!(account.history().len(), 1);
+}
@@ -0,0 +1,110 @@
+use simledger::account::Account;
+use simledger::execution::{ExecutionError, ExecutionResult};
+use simledger::id::{ClientId, RawClientId, RawTxId, TxId};
+use simledger::traits::execution::TransactionExecution;
+use simledger::transaction::{Transaction, TransactionTag};
+
+static CLIENT_ID_A: RawClientId = 12;
+static CLIENT_ID_B: RawClientId = 21;
+
+fn fake_tx(
+    id: RawTxId,
+    tag: TransactionTag,
+) -> Transaction {
+    Transaction {
+        id,
+        client_id: CLIENT_ID_A,
+        tag,
+        timestamp: None,
+        asset: None,
+    }
+}
+
+#[test]
+fn transaction_tag_amounts() {
+    for (tag, exp_result) in vec!(
+        (TransactionTag::Deposit(15000), Ok(ExecutionResult::Ok)),
+        (TransactionTag::Deposit(-50000), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Withdrawal(0), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Authorize(0), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Transfer(CLIENT_ID_B, -1), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Dispute(None), Ok(ExecutionResult::Ok)),
+        (TransactionTag::Dispute(Some(5000)), Ok(ExecutionResult::Ok)),
+        (TransactionTag::Resolve(Some(0)), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Chargeback(Some(-5000)), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::TransferReversal(Some(0)), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::CreditLimit(0), Ok(ExecutionResult::Ok)),
+        (TransactionTag::CreditLimit(-1), Err(ExecutionError::InvalidAmount)),
+        (TransactionTag::Representment, Ok(ExecutionResult::Ok)),
+    ).into_iter() {
+        assert_eq!(tag.assert_is_valid(), exp_result, "{:?}", tag);
+
+        assert_eq!(
+            Transaction::new(ClientId(CLIENT_ID_A), TxId(1), tag).map(|tx| tx.tag),
+            exp_result.map(|_| tag),
+        );
+    }
+}
+
+#[test]
+fn account_rejects_invalid_amounts() {
+    let mut account = Account::new(CLIENT_ID_A);
+
+    for (tx, exp_result, exp_available) in vec!(
+        (
+            fake_tx(1, TransactionTag::Deposit(-50000)),
+            Err(ExecutionError::InvalidAmount),
+            0,
+        ),
+        (
+            fake_tx(2, TransactionTag::Deposit(15000)),
+            Ok(ExecutionResult::NewAvailableBalance(15000)),
+            15000,
+        ),
+        (
+            fake_tx(3, TransactionTag::Withdrawal(0)),
+            Err(ExecutionError::InvalidAmount),
+            15000,
+        ),
+        (
+            fake_tx(4, TransactionTag::Withdrawal(-5000)),
+            Err(ExecutionError::InvalidAmount),
+            15000,
+        ),
+    ).into_iter() {
+        assert_eq!(account.execute_transaction(&tx), exp_result);
+        assert_eq!(account.amount_available(), exp_available);
+    }
+
+    // rejected transactions are not booked and may be submitted again
+    assert_eq!(account.book.len(), 1);
+    assert_eq!(account.history().len(), 1);
+}
+
+#[test]
+fn transaction_constructors() {
+    let client = ClientId(CLIENT_ID_A);
+
+    for (tx, exp_tag) in vec!(
+        (Transaction::deposit(client, TxId(1), 15000), Ok(TransactionTag::Deposit(15000))),
+        (Transaction::withdrawal(client, TxId(2), 0), Err(ExecutionError::InvalidAmount)),
+        (Transaction::dispute(client, TxId(1), None), Ok(TransactionTag::Dispute(None))),
+        (Transaction::chargeback(client, TxId(1), Some(-1)), Err(ExecutionError::InvalidAmount)),
+        (Transaction::capture(client, TxId(3)), Ok(TransactionTag::Capture)),
+        (
+            Transaction::transfer(client, TxId(4), ClientId(CLIENT_ID_B), 5000),
+            Ok(TransactionTag::Transfer(CLIENT_ID_B, 5000)),
+        ),
+        (Transaction::credit_limit(client, TxId(5), -1), Err(ExecutionError::InvalidAmount)),
+    ).into_iter() {
+        assert_eq!(tx.map(|tx| tx.tag), exp_tag);
+    }
+
+    let tx = Transaction::deposit(client, TxId(7), 15000).unwrap();
+
+    assert_eq!((tx.client(), tx.tx()), (client, TxId(7)));
+    assert_eq!(tx, fake_tx(7, TransactionTag::Deposit(15000)));
+
+    assert_eq!(" 12".parse(), Ok(client));
+    assert!("-1".parse::<TxId>().is_err());
+}